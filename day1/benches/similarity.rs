@@ -0,0 +1,41 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use day1::{calculate_similarity_for_lists, calculate_similarity_with_frequencies, parse_santas_lists, SantasLists};
+
+/// Two columns of five digit ids like the puzzle input, with a fixed seed so runs compare.
+fn generate_lists(entries: usize) -> SantasLists {
+    let mut rng = StdRng::seed_from_u64(2024);
+    let mut input = String::new();
+    for _ in 0..entries {
+        input += &format!("{}   {}\n", rng.gen_range(10000..100000), rng.gen_range(10000..100000));
+    }
+
+    parse_santas_lists(&input).unwrap()
+}
+
+fn bench_similarity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("similarity");
+    group.sample_size(10);
+
+    // The nested loop is quadratic, 10^6 entries would take hours.
+    for entries in [1_000, 10_000] {
+        let lists = generate_lists(entries);
+        group.bench_with_input(BenchmarkId::new("nested_loop", entries), &lists, |b, lists| {
+            b.iter(|| calculate_similarity_for_lists(black_box(lists)).unwrap())
+        });
+    }
+
+    for entries in [1_000, 10_000, 1_000_000] {
+        let lists = generate_lists(entries);
+        group.bench_with_input(BenchmarkId::new("frequency_map", entries), &lists, |b, lists| {
+            b.iter(|| calculate_similarity_with_frequencies(black_box(lists)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_similarity);
+criterion_main!(benches);
@@ -0,0 +1,268 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::ListsError;
+
+/// How many ids of each list are sorted in memory before being spilled to a temporary file.
+pub const DEFAULT_RUN_SIZE: usize = 1 << 20;
+/// How many runs are read at once by a merge, so the number of open files stays bounded however long the lists are.
+pub const MERGE_FAN_IN: usize = 64;
+
+/// The sums are 128 bits, as products and differences of 64 bit ids do not fit in 64 bits.
+#[derive(Debug, PartialEq)]
+pub struct ListTotals {
+    pub distance: i128,
+    pub similarity: i128
+}
+
+/// Same results as `calculate_distance_for_lists` and `calculate_similarity_with_frequencies`, for lists too big for memory.
+/// At most `run_size` ids per list are held at once: each full batch is sorted and written to an anonymous temporary file,
+/// then the sorted runs are merged, `MERGE_FAN_IN` at a time, until they can be read together while computing the totals.
+pub fn calculate_with_external_sort<R: BufRead>(reader: R, run_size: usize) -> io::Result<ListTotals> {
+
+    return calculate_with_fan_in(reader, run_size, MERGE_FAN_IN);
+}
+
+pub fn calculate_file_with_external_sort(path: &Path, run_size: usize) -> io::Result<ListTotals> {
+
+    return calculate_with_external_sort(BufReader::new(File::open(path)?), run_size);
+}
+
+fn calculate_with_fan_in<R: BufRead>(reader: R, run_size: usize, fan_in: usize) -> io::Result<ListTotals> {
+
+    let (left_runs, right_runs) = write_sorted_runs(reader, run_size)?;
+    let left_runs = reduce_runs(left_runs, fan_in)?;
+    let right_runs = reduce_runs(right_runs, fan_in)?;
+
+    let mut left_list = MergedRuns::new(&left_runs)?;
+    let mut right_list = MergedRuns::new(&right_runs)?;
+    let mut distance: i128 = 0;
+    while let (Some(left), Some(right)) = (left_list.next()?, right_list.next()?) {
+        distance += (left as i128 - right as i128).abs();
+    }
+
+    let mut left_list = MergedRuns::new(&left_runs)?;
+    let mut right_list = MergedRuns::new(&right_runs)?;
+    let mut similarity: i128 = 0;
+    let mut right = right_list.next()?;
+    while let Some(left) = left_list.next()? {
+        while right.is_some_and(|right| right < left) {
+            right = right_list.next()?;
+        }
+        let mut count: i128 = 0;
+        while right == Some(left) {
+            count += 1;
+            right = right_list.next()?;
+        }
+        // Equal ids of the left list all appear in a row, they each score the same count.
+        let mut occurrences: i128 = 1;
+        while left_list.peek() == Some(left) {
+            left_list.next()?;
+            occurrences += 1;
+        }
+        similarity += left as i128 * count * occurrences;
+    }
+
+    return Ok(ListTotals { distance, similarity });
+}
+
+fn write_sorted_runs<R: BufRead>(reader: R, run_size: usize) -> io::Result<(Vec<File>, Vec<File>)> {
+
+    let mut left_runs = Vec::new();
+    let mut right_runs = Vec::new();
+    let mut left_batch: Vec<i64> = Vec::new();
+    let mut right_batch: Vec<i64> = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (left, right) = read_pair(index + 1, &line).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+        left_batch.push(left);
+        right_batch.push(right);
+
+        if left_batch.len() >= run_size.max(1) {
+            left_runs.push(write_run(&mut left_batch)?);
+            right_runs.push(write_run(&mut right_batch)?);
+        }
+    }
+    if !left_batch.is_empty() {
+        left_runs.push(write_run(&mut left_batch)?);
+        right_runs.push(write_run(&mut right_batch)?);
+    }
+
+    return Ok((left_runs, right_runs));
+}
+
+fn read_pair(line_number: usize, line: &str) -> Result<(i64, i64), ListsError> {
+    let mut numbers = Vec::new();
+    for value in line.split_whitespace() {
+        match value.parse() {
+            Ok(number) => numbers.push(number),
+            Err(_) => return Err(ListsError::InvalidNumber { line: line_number, value: String::from(value) })
+        }
+    }
+    if numbers.len() != 2 {
+        return Err(ListsError::RaggedRow { line: line_number, expected: 2, found: numbers.len() });
+    }
+    return Ok((numbers[0], numbers[1]));
+}
+
+/// Sorts and empties the batch into a temporary file, deleted by the system once closed.
+fn write_run(batch: &mut Vec<i64>) -> io::Result<File> {
+    batch.sort_unstable();
+
+    let mut writer = BufWriter::new(tempfile::tempfile()?);
+    for id in batch.iter() {
+        writer.write_all(&id.to_le_bytes())?;
+    }
+    batch.clear();
+
+    return writer.into_inner().map_err(|error| error.into_error());
+}
+
+/// Merges groups of `fan_in` runs into longer runs, pass after pass, until no more than `fan_in` are left.
+fn reduce_runs(mut runs: Vec<File>, fan_in: usize) -> io::Result<Vec<File>> {
+    let fan_in = fan_in.max(2);
+    while runs.len() > fan_in {
+        let mut merged_runs = Vec::new();
+        for group in runs.chunks(fan_in) {
+            merged_runs.push(merge_runs(group)?);
+        }
+        runs = merged_runs;
+    }
+    return Ok(runs);
+}
+
+fn merge_runs(runs: &[File]) -> io::Result<File> {
+    let mut merged = MergedRuns::new(runs)?;
+
+    let mut writer = BufWriter::new(tempfile::tempfile()?);
+    while let Some(id) = merged.next()? {
+        writer.write_all(&id.to_le_bytes())?;
+    }
+
+    return writer.into_inner().map_err(|error| error.into_error());
+}
+
+struct RunReader {
+    reader: BufReader<File>
+}
+
+impl RunReader {
+
+    fn new(run: &File) -> io::Result<RunReader> {
+        let mut file = run.try_clone()?;
+        file.seek(SeekFrom::Start(0))?;
+        return Ok(RunReader { reader: BufReader::new(file) });
+    }
+
+    fn next(&mut self) -> io::Result<Option<i64>> {
+        let mut bytes = [0; 8];
+        return match self.reader.read_exact(&mut bytes) {
+            Ok(()) => Ok(Some(i64::from_le_bytes(bytes))),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(error) => Err(error)
+        };
+    }
+}
+
+/// Reads sorted runs back as a single sorted sequence, keeping the smallest unread id of each run in a heap.
+struct MergedRuns {
+    runs: Vec<RunReader>,
+    heap: BinaryHeap<Reverse<(i64, usize)>>
+}
+
+impl MergedRuns {
+
+    fn new(runs: &[File]) -> io::Result<MergedRuns> {
+        let mut merged = MergedRuns { runs: Vec::new(), heap: BinaryHeap::new() };
+        for run in runs {
+            let mut reader = RunReader::new(run)?;
+            if let Some(id) = reader.next()? {
+                merged.heap.push(Reverse((id, merged.runs.len())));
+            }
+            merged.runs.push(reader);
+        }
+        return Ok(merged);
+    }
+
+    fn peek(&self) -> Option<i64> {
+        return self.heap.peek().map(|Reverse((id, _))| *id);
+    }
+
+    fn next(&mut self) -> io::Result<Option<i64>> {
+        let Reverse((id, run)) = match self.heap.pop() {
+            Some(smallest) => smallest,
+            None => return Ok(None)
+        };
+        if let Some(next_id) = self.runs[run].next()? {
+            self.heap.push(Reverse((next_id, run)));
+        }
+        return Ok(Some(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_example_with_small_runs_totals_match_in_memory_results() {
+        let input = "3   4
+4   3
+2   5
+1   3
+3   9
+3   3";
+
+        let result = calculate_with_external_sort(input.as_bytes(), 2).unwrap();
+
+        assert_eq!(result, ListTotals { distance: 11, similarity: 31 });
+    }
+
+    #[test]
+    fn given_ids_beyond_i32_totals_do_not_overflow() {
+        let input = "5000000000 5000000000
+1 5000000000";
+
+        let result = calculate_with_external_sort(input.as_bytes(), 1).unwrap();
+
+        assert_eq!(result, ListTotals { distance: 4999999999, similarity: 10000000000 });
+    }
+
+    #[test]
+    fn given_more_runs_than_the_fan_in_they_are_merged_in_passes() {
+        let mut input = String::new();
+        for id in 0..50 {
+            input += &format!("{} {}\n", 49 - id, id);
+        }
+
+        let runs = write_sorted_runs(input.as_bytes(), 1).unwrap().0;
+        let reduced = reduce_runs(runs, 3).unwrap();
+        let result = calculate_with_fan_in(input.as_bytes(), 1, 3).unwrap();
+
+        assert_eq!(reduced.len(), 2);
+        assert_eq!(result, ListTotals { distance: 0, similarity: (0..50).sum() });
+    }
+
+    #[test]
+    fn given_extreme_ids_totals_do_not_overflow() {
+        let input = format!("{} {}\n{} {}", i64::MIN, i64::MAX, i64::MAX, i64::MAX);
+
+        let result = calculate_with_external_sort(input.as_bytes(), 1).unwrap();
+
+        assert_eq!(result, ListTotals { distance: i64::MAX as i128 - i64::MIN as i128, similarity: 2 * i64::MAX as i128 });
+    }
+
+    #[test]
+    fn given_three_columns_external_sort_fails() {
+        let result = calculate_with_external_sort("1 2 3".as_bytes(), 10);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,24 +1,54 @@
-fn calculate_distance_between_lists(input: &str) -> i32 {
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead};
 
-    let lists = create_santas_lists(input);
+use serde::Serialize;
 
-    let mut sum = 0;
-    for i in 0..lists.left_list.len() {
-        let difference = lists.left_list[i] - lists.right_list[i];
+pub mod external_sort;
+
+pub fn calculate_distance_between_lists(input: &str) -> Result<i64, ListsError> {
+
+    let lists = parse_santas_lists(input)?;
+
+    return calculate_distance_for_lists(&lists);
+}
+
+pub fn calculate_distance_for_lists(lists: &SantasLists) -> Result<i64, ListsError> {
+
+    return calculate_distance_between_columns(lists, 0, 1);
+}
+
+/// The differences are taken in 64 bits, since two ids far apart differ by more than an `i32` holds.
+pub fn calculate_distance_between_columns(lists: &SantasLists, left: usize, right: usize) -> Result<i64, ListsError> {
+
+    let left_list = lists.column(left)?;
+    let right_list = lists.column(right)?;
+
+    let mut sum: i64 = 0;
+    for i in 0..left_list.len() {
+        let difference = left_list[i] as i64 - right_list[i] as i64;
         sum += difference.abs();
     }
 
-    return sum
+    return Ok(sum);
+}
+
+pub fn calculate_similarity_between_lists(input: &str) -> Result<i32, ListsError> {
+
+    let lists = parse_santas_lists(input)?;
+
+    return calculate_similarity_for_lists(&lists);
 }
 
-fn calculate_similarity_between_lists(input: &str) -> i32 {
+pub fn calculate_similarity_for_lists(lists: &SantasLists) -> Result<i32, ListsError> {
 
-    let lists = create_santas_lists(input);
+    let left_list = lists.column(0)?;
+    let right_list = lists.column(1)?;
 
     let mut similarity_sum = 0;
-    for element in &lists.left_list {
+    for element in left_list {
         let mut count = 0;
-        for other_element in &lists.right_list {
+        for other_element in right_list {
             if element == other_element {
                 count = count + 1;
             }
@@ -26,42 +56,364 @@ fn calculate_similarity_between_lists(input: &str) -> i32 {
         similarity_sum += element * count;
     }
 
-    return similarity_sum;
+    return Ok(similarity_sum);
 }
 
-fn create_santas_lists(input: &str) -> SantasLists {
+pub fn calculate_similarity_with_frequencies(lists: &SantasLists) -> Result<i64, ListsError> {
+
+    return calculate_similarity_between_columns(lists, 0, 1);
+}
 
-    let mut left_list: Vec<i32> = Vec::new();
-    let mut right_list: Vec<i32> = Vec::new();
+/// Counts every id of the right column once, so the similarity takes linear time instead of comparing every pair.
+/// The sum is kept in 64 bits since it grows with the square of the list length.
+pub fn calculate_similarity_between_columns(lists: &SantasLists, left: usize, right: usize) -> Result<i64, ListsError> {
 
-    for line in input.lines() {
-        let pair_of_numbers: Numbers = read_line_of_strings(line);
-        left_list.push(pair_of_numbers.first_number);
-        right_list.push(pair_of_numbers.second_number);
+    let mut frequencies: HashMap<i32, i64> = HashMap::new();
+    for element in lists.column(right)? {
+        *frequencies.entry(*element).or_insert(0) += 1;
     }
 
-    left_list.sort();
-    right_list.sort();
+    let mut similarity_sum: i64 = 0;
+    for element in lists.column(left)? {
+        let count = frequencies.get(element).unwrap_or(&0);
+        similarity_sum += *element as i64 * count;
+    }
+
+    return Ok(similarity_sum);
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ColumnPairResult {
+    pub left: usize,
+    pub right: usize,
+    pub distance: i64,
+    pub similarity: i64
+}
+
+/// Distance and similarity for every chosen pair of columns.
+pub fn calculate_for_column_pairs(lists: &SantasLists, pairs: &[(usize, usize)]) -> Result<Vec<ColumnPairResult>, ListsError> {
+
+    let mut results = Vec::new();
+    for (left, right) in pairs {
+        results.push(ColumnPairResult {
+            left: *left,
+            right: *right,
+            distance: calculate_distance_between_columns(lists, *left, *right)?,
+            similarity: calculate_similarity_between_columns(lists, *left, *right)?
+        });
+    }
+
+    return Ok(results);
+}
+
+pub fn parse_santas_lists(input: &str) -> Result<SantasLists, ListsError> {
+
+    let mut builder = ListsBuilder::new();
+
+    for (index, line) in input.lines().enumerate() {
+        builder.add_line(index + 1, line)?;
+    }
+
+    return Ok(builder.build());
+}
+
+/// Builds the lists one line at a time, so the input never has to be held in memory as a whole.
+/// A malformed line is reported as an `InvalidData` error.
+pub fn read_santas_lists<R: BufRead>(reader: R) -> io::Result<SantasLists> {
+
+    let mut builder = ListsBuilder::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        builder.add_line(index + 1, &line).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+    }
+
+    return Ok(builder.build());
+}
+
+/// One sorted list per column of the input.
+pub struct SantasLists {
+    columns: Vec<Vec<i32>>
+}
+
+impl SantasLists {
+
+    pub fn column_count(&self) -> usize {
+        return self.columns.len();
+    }
+
+    pub fn column(&self, index: usize) -> Result<&[i32], ListsError> {
+        return match self.columns.get(index) {
+            Some(column) => Ok(column),
+            None => Err(ListsError::MissingColumn { index, columns: self.columns.len() })
+        };
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ListsError {
+    /// A line does not have as many columns as the first one.
+    RaggedRow { line: usize, expected: usize, found: usize },
+    InvalidNumber { line: usize, value: String },
+    MissingColumn { index: usize, columns: usize }
+}
+
+impl fmt::Display for ListsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            ListsError::RaggedRow { line, expected, found } => write!(f, "Line {} has {} columns, expected {}", line, found, expected),
+            ListsError::InvalidNumber { line, value } => write!(f, "Line {} has an invalid number: {}", line, value),
+            ListsError::MissingColumn { index, columns } => write!(f, "There is no column {}, the lists have {} columns", index, columns)
+        };
+    }
+}
+
+impl std::error::Error for ListsError {}
+
+/// The column count is set by the first line that is not blank.
+/// Without any such line there are two empty lists, as in the puzzle, so every total is 0.
+struct ListsBuilder {
+    columns: Vec<Vec<i32>>
+}
+
+impl ListsBuilder {
+
+    fn new() -> ListsBuilder {
+        return ListsBuilder { columns: Vec::new() };
+    }
+
+    fn add_line(&mut self, line_number: usize, line: &str) -> Result<(), ListsError> {
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+
+        let numbers = read_line_of_numbers(line_number, line)?;
+        if self.columns.is_empty() {
+            self.columns = vec![Vec::new(); numbers.len()];
+        }
+        if numbers.len() != self.columns.len() {
+            return Err(ListsError::RaggedRow { line: line_number, expected: self.columns.len(), found: numbers.len() });
+        }
+
+        for (column, number) in self.columns.iter_mut().zip(numbers) {
+            column.push(number);
+        }
+        return Ok(());
+    }
+
+    fn build(mut self) -> SantasLists {
+        if self.columns.is_empty() {
+            self.columns = vec![Vec::new(); 2];
+        }
+        for column in self.columns.iter_mut() {
+            column.sort();
+        }
+        return SantasLists { columns: self.columns };
+    }
+}
+
+/// Columns can be separated by any amount of spaces or tabs.
+fn read_line_of_numbers(line_number: usize, line: &str) -> Result<Vec<i32>, ListsError> {
+    let mut numbers = Vec::new();
+    for value in line.split_whitespace() {
+        match value.parse() {
+            Ok(number) => numbers.push(number),
+            Err(_) => return Err(ListsError::InvalidNumber { line: line_number, value: String::from(value) })
+        }
+    }
+    return Ok(numbers);
+}
+
+const LARGEST_GAPS: usize = 5;
+const HISTOGRAM_BUCKETS: usize = 10;
+
+/// What disagrees between two columns of the lists, to audit the total distance.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ReconciliationReport {
+    pub left: usize,
+    pub right: usize,
+    pub only_in_left: Vec<i32>,
+    pub only_in_right: Vec<i32>,
+    pub duplicates_in_left: Vec<IdCount>,
+    pub duplicates_in_right: Vec<IdCount>,
+    pub total_distance: i64,
+    pub mean_difference: f64,
+    pub median_difference: f64,
+    /// The pairs furthest apart, largest first.
+    pub largest_gaps: Vec<Gap>,
+    pub histogram: Vec<HistogramBucket>
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct IdCount {
+    pub id: i32,
+    pub count: usize
+}
+
+/// The `position`th smallest id of each column, which are compared with each other.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Gap {
+    pub position: usize,
+    pub left_id: i32,
+    pub right_id: i32,
+    pub distance: i64
+}
+
+/// Distances from `start` to `end`, both included.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct HistogramBucket {
+    pub start: i64,
+    pub end: i64,
+    pub count: usize
+}
+
+pub fn reconcile_lists(lists: &SantasLists) -> Result<ReconciliationReport, ListsError> {
+
+    return reconcile_columns(lists, 0, 1);
+}
+
+pub fn reconcile_columns(lists: &SantasLists, left: usize, right: usize) -> Result<ReconciliationReport, ListsError> {
+
+    let left_list = lists.column(left)?;
+    let right_list = lists.column(right)?;
+    let left_counts = count_ids(left_list);
+    let right_counts = count_ids(right_list);
+
+    let mut gaps = Vec::new();
+    for i in 0..left_list.len() {
+        let distance = (left_list[i] as i64 - right_list[i] as i64).abs();
+        gaps.push(Gap { position: i, left_id: left_list[i], right_id: right_list[i], distance });
+    }
+
+    let mut distances: Vec<i64> = gaps.iter().map(|gap| gap.distance).collect();
+    distances.sort();
+    let total_distance: i64 = distances.iter().sum();
+
+    gaps.sort_by(|a, b| b.distance.cmp(&a.distance).then(a.position.cmp(&b.position)));
+    gaps.truncate(LARGEST_GAPS);
+
+    return Ok(ReconciliationReport {
+        left,
+        right,
+        only_in_left: find_missing_ids(&left_counts, &right_counts),
+        only_in_right: find_missing_ids(&right_counts, &left_counts),
+        duplicates_in_left: find_duplicates(&left_counts),
+        duplicates_in_right: find_duplicates(&right_counts),
+        total_distance,
+        mean_difference: match distances.len() {
+            0 => 0.0,
+            count => total_distance as f64 / count as f64
+        },
+        median_difference: calculate_median(&distances),
+        largest_gaps: gaps,
+        histogram: create_histogram(&distances)
+    });
+}
+
+fn count_ids(list: &[i32]) -> HashMap<i32, usize> {
+    let mut counts = HashMap::new();
+    for id in list {
+        *counts.entry(*id).or_insert(0) += 1;
+    }
+    return counts;
+}
+
+fn find_missing_ids(counts: &HashMap<i32, usize>, other_counts: &HashMap<i32, usize>) -> Vec<i32> {
+    let mut missing: Vec<i32> = counts.keys().filter(|id| !other_counts.contains_key(id)).copied().collect();
+    missing.sort();
+    return missing;
+}
+
+fn find_duplicates(counts: &HashMap<i32, usize>) -> Vec<IdCount> {
+    let mut duplicates: Vec<IdCount> = counts.iter()
+        .filter(|(_, count)| **count > 1)
+        .map(|(id, count)| IdCount { id: *id, count: *count })
+        .collect();
+    duplicates.sort_by_key(|duplicate| duplicate.id);
+    return duplicates;
+}
 
-    return SantasLists {left_list, right_list};
+/// `distances` must be sorted.
+fn calculate_median(distances: &[i64]) -> f64 {
+    let middle = distances.len() / 2;
+    return match distances.len() {
+        0 => 0.0,
+        length if length % 2 == 0 => (distances[middle - 1] + distances[middle]) as f64 / 2.0,
+        _ => distances[middle] as f64
+    };
 }
 
-struct SantasLists {
-    left_list: Vec<i32>,
-    right_list: Vec<i32>
+/// Splits `0..=max` into buckets of equal width, `distances` must be sorted.
+fn create_histogram(distances: &[i64]) -> Vec<HistogramBucket> {
+    let max = match distances.last() {
+        Some(max) => *max,
+        None => return Vec::new()
+    };
+    let width = ((max + 1) as u64).div_ceil(HISTOGRAM_BUCKETS as u64) as i64;
+
+    let mut histogram = Vec::new();
+    let mut start = 0;
+    while start <= max {
+        histogram.push(HistogramBucket { start, end: start + width - 1, count: 0 });
+        start += width;
+    }
+    for distance in distances {
+        histogram[(distance / width) as usize].count += 1;
+    }
+    return histogram;
 }
 
-struct Numbers {
-    first_number: i32,
-    second_number: i32
+impl ReconciliationReport {
+
+    pub fn to_json(&self) -> String {
+        return serde_json::to_string_pretty(self).unwrap();
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Reconciliation of column {} with column {}\n", self.left, self.right);
+
+        text += &format!("\nOnly in column {}: {}\n", self.left, format_ids(&self.only_in_left));
+        text += &format!("Only in column {}: {}\n", self.right, format_ids(&self.only_in_right));
+        text += &format!("Duplicates in column {}: {}\n", self.left, format_duplicates(&self.duplicates_in_left));
+        text += &format!("Duplicates in column {}: {}\n", self.right, format_duplicates(&self.duplicates_in_right));
+
+        text += &format!("\nTotal distance: {}\n", self.total_distance);
+        text += &format!("Mean difference: {:.2}\n", self.mean_difference);
+        text += &format!("Median difference: {:.1}\n", self.median_difference);
+
+        text += "\nLargest gaps:\n";
+        for gap in &self.largest_gaps {
+            text += &format!("  #{:<6} {:>10} {:>10}  distance {}\n", gap.position, gap.left_id, gap.right_id, gap.distance);
+        }
+
+        text += "\nDistances:\n";
+        let largest_count = self.histogram.iter().map(|bucket| bucket.count).max().unwrap_or(0);
+        for bucket in &self.histogram {
+            let bar_length = match largest_count {
+                0 => 0,
+                _ => (bucket.count * 40).div_ceil(largest_count)
+            };
+            let line = format!("  {:>8} - {:<8} {:>7} {}", bucket.start, bucket.end, bucket.count, "#".repeat(bar_length));
+            text += line.trim_end();
+            text += "\n";
+        }
+
+        return text.trim_end().to_string();
+    }
 }
 
-fn read_line_of_strings(line: &str) -> Numbers {
-    let position_of_first_space = line.find(' ').unwrap();
-    let number_for_first_list = &line[..position_of_first_space];
-    let number_for_second_list = &line[position_of_first_space..].trim();
+fn format_ids(ids: &[i32]) -> String {
+    if ids.is_empty() {
+        return String::from("none");
+    }
+    return ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(", ");
+}
 
-    return Numbers {first_number: number_for_first_list.parse().unwrap(), second_number: number_for_second_list.parse().unwrap()};
+fn format_duplicates(duplicates: &[IdCount]) -> String {
+    if duplicates.is_empty() {
+        return String::from("none");
+    }
+    return duplicates.iter().map(|duplicate| format!("{} (x{})", duplicate.id, duplicate.count)).collect::<Vec<String>>().join(", ");
 }
 
 #[cfg(test)]
@@ -77,7 +429,7 @@ mod tests {
 3   9
 3   3";
 
-        let distance_between_lists = calculate_distance_between_lists(input);
+        let distance_between_lists = calculate_distance_between_lists(input).unwrap();
 
         assert_eq!(distance_between_lists, 11);
     }
@@ -91,8 +443,167 @@ mod tests {
 3   9
 3   3";
 
-        let similarity = calculate_similarity_between_lists(input);
+        let similarity = calculate_similarity_between_lists(input).unwrap();
 
         assert_eq!(similarity, 31);
     }
+
+    #[test]
+    fn given_example_from_reader_lists_give_same_results() {
+        let input = "3   4
+4   3
+2   5
+1   3
+3   9
+3   3
+";
+
+        let lists = read_santas_lists(input.as_bytes()).unwrap();
+
+        assert_eq!(calculate_distance_for_lists(&lists), Ok(11));
+        assert_eq!(calculate_similarity_for_lists(&lists), Ok(31));
+    }
+
+    #[test]
+    fn example_similarity_with_frequencies_is_thirtyone() {
+        let input = "3   4
+4   3
+2   5
+1   3
+3   9
+3   3";
+
+        let lists = parse_santas_lists(input).unwrap();
+
+        assert_eq!(calculate_similarity_with_frequencies(&lists), Ok(31));
+    }
+
+    #[test]
+    fn given_large_ids_similarity_with_frequencies_does_not_overflow() {
+        let input = "99999   99999
+99999   99999";
+
+        let lists = parse_santas_lists(input).unwrap();
+
+        assert_eq!(calculate_similarity_with_frequencies(&lists), Ok(4 * 99999));
+    }
+
+    #[test]
+    fn given_tabs_and_many_spaces_lists_are_read() {
+        let input = "3\t4
+4     3";
+
+        let lists = parse_santas_lists(input).unwrap();
+
+        assert_eq!(lists.column(0), Ok(&[3, 4][..]));
+        assert_eq!(lists.column(1), Ok(&[3, 4][..]));
+    }
+
+    #[test]
+    fn given_three_columns_pairs_are_calculated() {
+        let input = "1 2 5
+3 3 3";
+
+        let lists = parse_santas_lists(input).unwrap();
+        let result = calculate_for_column_pairs(&lists, &[(0, 1), (0, 2)]).unwrap();
+
+        assert_eq!(result[0], ColumnPairResult { left: 0, right: 1, distance: 1, similarity: 3 });
+        assert_eq!(result[1], ColumnPairResult { left: 0, right: 2, distance: 4, similarity: 3 });
+    }
+
+    #[test]
+    fn given_empty_input_totals_are_zero() {
+        let lists = parse_santas_lists("").unwrap();
+
+        assert_eq!(calculate_distance_between_lists(""), Ok(0));
+        assert_eq!(calculate_similarity_between_lists("\n"), Ok(0));
+        assert_eq!(calculate_similarity_with_frequencies(&lists), Ok(0));
+        assert_eq!(reconcile_lists(&lists).unwrap().total_distance, 0);
+    }
+
+    #[test]
+    fn given_ids_far_apart_distance_does_not_overflow() {
+        let lists = parse_santas_lists("-2000000000 2000000000\n-1 1").unwrap();
+
+        assert_eq!(calculate_distance_for_lists(&lists), Ok(4000000002));
+        assert_eq!(calculate_for_column_pairs(&lists, &[(0, 1)]).unwrap()[0].distance, 4000000002);
+    }
+
+    #[test]
+    fn given_single_column_pair_totals_fail() {
+        let lists = parse_santas_lists("1\n2").unwrap();
+
+        assert_eq!(calculate_distance_for_lists(&lists), Err(ListsError::MissingColumn { index: 1, columns: 1 }));
+    }
+
+    #[test]
+    fn given_ragged_row_parsing_fails() {
+        let input = "1 2 5
+3 3";
+
+        let result = parse_santas_lists(input);
+
+        assert_eq!(result.err(), Some(ListsError::RaggedRow { line: 2, expected: 3, found: 2 }));
+    }
+
+    #[test]
+    fn given_bad_number_parsing_fails() {
+        let input = "1 x";
+
+        let result = parse_santas_lists(input);
+
+        assert_eq!(result.err(), Some(ListsError::InvalidNumber { line: 1, value: String::from("x") }));
+    }
+
+    #[test]
+    fn given_missing_column_pairs_are_rejected() {
+        let lists = parse_santas_lists("1 2").unwrap();
+
+        let result = calculate_for_column_pairs(&lists, &[(0, 2)]);
+
+        assert_eq!(result.err(), Some(ListsError::MissingColumn { index: 2, columns: 2 }));
+    }
+
+    #[test]
+    fn given_example_reconciliation_finds_disagreements() {
+        let input = "3   4
+4   3
+2   5
+1   3
+3   9
+3   3";
+
+        let lists = parse_santas_lists(input).unwrap();
+        let report = reconcile_lists(&lists).unwrap();
+
+        assert_eq!(report.only_in_left, [1, 2]);
+        assert_eq!(report.only_in_right, [5, 9]);
+        assert_eq!(report.duplicates_in_left, [IdCount { id: 3, count: 3 }]);
+        assert_eq!(report.duplicates_in_right, [IdCount { id: 3, count: 3 }]);
+        assert_eq!(report.total_distance, 11);
+        assert_eq!(report.median_difference, 1.5);
+        assert_eq!(report.largest_gaps[0], Gap { position: 5, left_id: 4, right_id: 9, distance: 5 });
+        assert_eq!(report.histogram.len(), 6);
+        assert_eq!(report.histogram[1], HistogramBucket { start: 1, end: 1, count: 2 });
+    }
+
+    #[test]
+    fn given_report_json_has_every_field() {
+        let lists = parse_santas_lists("1 2").unwrap();
+
+        let json = reconcile_lists(&lists).unwrap().to_json();
+
+        assert!(json.contains("\"only_in_left\": [\n    1\n  ]"));
+        assert!(json.contains("\"median_difference\": 1.0"));
+    }
+
+    #[test]
+    fn given_report_text_shows_histogram_bars() {
+        let lists = parse_santas_lists("1 2\n5 5").unwrap();
+
+        let text = reconcile_lists(&lists).unwrap().to_text();
+
+        assert!(text.contains("Only in column 0: 1"));
+        assert!(text.contains("       0 - 0              1 ########################################"));
+    }
 }
@@ -1,4 +1,11 @@
-fn get_amount_of_safe_reports(input: &str) -> i32 {
+use std::fmt;
+use std::io::{self, BufRead};
+
+use serde::Serialize;
+
+pub mod rules;
+
+pub fn get_amount_of_safe_reports(input: &str) -> i32 {
 
     let reports = create_reports(input);
 
@@ -7,6 +14,33 @@ fn get_amount_of_safe_reports(input: &str) -> i32 {
     return safe_reports_amount;
 }
 
+pub fn get_amount_of_safe_reports_from_reader<R: BufRead>(reader: R) -> io::Result<i32> {
+
+    return Ok(count_safe_reports_from_reader(reader, &[PROBLEM_DAMPENER])?[0]);
+}
+
+/// Checks each report as soon as its line is read, so only one report is kept in memory at a time.
+/// Returns the amount of safe reports for each validator, in the same order.
+pub fn count_safe_reports_from_reader<R: BufRead>(reader: R, validators: &[ReportValidator]) -> io::Result<Vec<i32>> {
+
+    let mut amounts_of_safe_reports = vec![0; validators.len()];
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let report = read_line_of_strings(&line);
+        for (i, validator) in validators.iter().enumerate() {
+            if validator.is_safe(&report) {
+                amounts_of_safe_reports[i] += 1;
+            }
+        }
+    }
+
+    return Ok(amounts_of_safe_reports);
+}
+
 fn read_line_of_strings(line: &str) -> Vec<i32> {
     return line
         .split(' ')
@@ -39,119 +73,311 @@ fn read_reports(report_list: Vec<Vec<i32>>) -> i32 {
     return amount_of_safe_reports;
 }
 
+/// The puzzle's rules with the Problem Dampener, which tolerates one bad level.
 fn is_report_safe(report: &Vec<i32>) -> bool {
 
-    if report.len() == 0 || report.len() == 1 {
-        return true
+    return PROBLEM_DAMPENER.is_safe(report);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Monotonicity {
+    Increasing,
+    Decreasing,
+    /// Increasing or decreasing, as long as the whole report goes the same way.
+    Either
+}
+
+/// A report is safe when every step between consecutive levels goes in the required direction and
+/// is between `min_step` and `max_step` (in absolute value), after removing at most `tolerance` levels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReportValidator {
+    pub min_step: i32,
+    pub max_step: i32,
+    pub monotonicity: Monotonicity,
+    pub tolerance: usize
+}
+
+pub const STRICT_RULES: ReportValidator = ReportValidator { min_step: 1, max_step: 3, monotonicity: Monotonicity::Either, tolerance: 0 };
+pub const PROBLEM_DAMPENER: ReportValidator = ReportValidator { tolerance: 1, ..STRICT_RULES };
+
+impl ReportValidator {
+
+    pub fn is_safe(&self, report: &[i32]) -> bool {
+        return self.count_levels_to_remove(report).is_some();
     }
-    
-    let mut previous_number: Option<i32> = None;
-    let mut is_ascending: Option<bool> = None;
-    let mut asc_count = 0;
-    let mut desc_count = 0;
 
-    for number in report.iter() {
-        match previous_number {
-            None => {
-                previous_number = Some(*number);
-                continue;
-            }
-            Some(prev_number) => {
-                if *number > prev_number {
-                    asc_count += 1;
-                }
-                else if *number < prev_number {
-                    desc_count += 1;
-                }
-            }
+    /// The fewest levels to remove to make the report safe, or `None` when more than `tolerance` would be needed.
+    pub fn count_levels_to_remove(&self, report: &[i32]) -> Option<usize> {
+        let removals = match self.monotonicity {
+            Monotonicity::Either => self.count_removals_in_direction(report, Monotonicity::Increasing)
+                .min(self.count_removals_in_direction(report, Monotonicity::Decreasing)),
+            direction => self.count_removals_in_direction(report, direction)
+        };
+
+        if removals <= self.tolerance {
+            return Some(removals);
         }
-        previous_number = Some(*number);
+        return None;
     }
-    
-    //println!("Asc count {} and desc count {}", asc_count, desc_count);
-    is_ascending = Some(asc_count > desc_count);
 
-    let mut bad_indices: Vec<usize> = Vec::new();
-    for i in 0..report.len() - 1 {
+    pub fn is_step_allowed(&self, first_level: i32, second_level: i32, direction: Monotonicity) -> bool {
+        let difference = second_level - first_level;
+        let goes_right_way = match direction {
+            Monotonicity::Increasing => difference > 0,
+            Monotonicity::Decreasing => difference < 0,
+            Monotonicity::Either => true
+        };
 
-        match is_ascending {
-            None => panic!(),
-            Some(asc) => {
-                let first_number = report[i];
-                let second_number = report[i+1];
+        return goes_right_way && difference.abs() >= self.min_step && difference.abs() <= self.max_step;
+    }
 
-                if asc && first_number > second_number {
-                    bad_indices.push(i);
-                    bad_indices.push(i+1);
-                }
-                else if !asc && second_number > first_number {
-                    bad_indices.push(i);
-                    bad_indices.push(i+1);
-                }
+    /// `removals[i]` is the fewest removals among the first i levels when level i is kept.
+    /// Keeping j then i removes everything in between, so only the `tolerance + 1` levels before i are worth looking at
+    /// and the whole check is O(n * tolerance) instead of trying every combination of removals.
+    /// The result is exact whenever it is within the tolerance, which is all that matters.
+    fn count_removals_in_direction(&self, report: &[i32], direction: Monotonicity) -> usize {
+        return self.find_levels_to_remove(report, direction).len();
+    }
 
-                let mut difference = second_number - first_number;
-                difference = difference.abs();
+    /// The indices of the levels to remove, found by following back which level was kept before each kept level.
+    fn find_levels_to_remove(&self, report: &[i32], direction: Monotonicity) -> Vec<usize> {
+        if report.len() <= 1 {
+            return Vec::new();
+        }
 
-                if difference < 1 || difference > 3 {
-                    bad_indices.push(i);
-                    bad_indices.push(i+1);
+        let mut removals: Vec<usize> = Vec::new();
+        let mut previous_kept: Vec<Option<usize>> = Vec::new();
+        let mut last_kept = 0;
+        let mut fewest_removals = report.len();
+        for i in 0..report.len() {
+            let mut removals_keeping_i = i;
+            let mut kept_before_i = None;
+            for j in i.saturating_sub(self.tolerance + 1)..i {
+                if self.is_step_allowed(report[j], report[i], direction) && removals[j] + i - j - 1 < removals_keeping_i {
+                    removals_keeping_i = removals[j] + i - j - 1;
+                    kept_before_i = Some(j);
                 }
             }
+            removals.push(removals_keeping_i);
+            previous_kept.push(kept_before_i);
+            if removals_keeping_i + report.len() - 1 - i < fewest_removals {
+                fewest_removals = removals_keeping_i + report.len() - 1 - i;
+                last_kept = i;
+            }
         }
+
+        let mut is_kept = vec![false; report.len()];
+        let mut kept = Some(last_kept);
+        while let Some(index) = kept {
+            is_kept[index] = true;
+            kept = previous_kept[index];
+        }
+
+        return (0..report.len()).filter(|index| !is_kept[*index]).collect();
     }
 
-    if bad_indices.len() == 0 {
-        return true;
+    /// Which way the report is meant to go: the required direction, or for `Either` the one needing the fewest removals.
+    /// On a tie, the direction of the first step that changes the level.
+    fn find_intended_direction(&self, report: &[i32]) -> Monotonicity {
+        if self.monotonicity != Monotonicity::Either {
+            return self.monotonicity;
+        }
+
+        let increasing_removals = self.count_removals_in_direction(report, Monotonicity::Increasing);
+        let decreasing_removals = self.count_removals_in_direction(report, Monotonicity::Decreasing);
+        if increasing_removals != decreasing_removals {
+            return match increasing_removals < decreasing_removals {
+                true => Monotonicity::Increasing,
+                false => Monotonicity::Decreasing
+            };
+        }
+
+        for i in 0..report.len().saturating_sub(1) {
+            if report[i + 1] < report[i] {
+                return Monotonicity::Decreasing;
+            }
+            if report[i + 1] > report[i] {
+                return Monotonicity::Increasing;
+            }
+        }
+        return Monotonicity::Increasing;
     }
 
-    if bad_indices.len() > 4 {
-        return false;
+    /// The rule broken by a step, if any. Going the wrong way is reported before the size of the step.
+    pub fn check_step(&self, first_level: i32, second_level: i32, direction: Monotonicity) -> Option<Rule> {
+        let difference = second_level - first_level;
+        let goes_wrong_way = match direction {
+            Monotonicity::Increasing => difference < 0,
+            Monotonicity::Decreasing => difference > 0,
+            Monotonicity::Either => false
+        };
+
+        if goes_wrong_way {
+            return Some(Rule::DirectionChange);
+        }
+        if difference.abs() < self.min_step {
+            return Some(Rule::StepTooSmall);
+        }
+        if difference.abs() > self.max_step {
+            return Some(Rule::StepTooLarge);
+        }
+        return None;
     }
 
-    
-    println!("{:?}", report);
-    println!("{:?}", bad_indices);
-    for i in 0..bad_indices.len() {
-        let mut report_copy = report.to_vec();
-        report_copy.remove(bad_indices[i]);
-        println!("Removing index {} ", bad_indices[i]);
-        println!("New report {:?}", report_copy);
-        if is_report_safe_strict(&report_copy, is_ascending.unwrap()) {
-            return true;
+    fn find_violations(&self, report: &[i32], direction: Monotonicity) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for i in 0..report.len().saturating_sub(1) {
+            if let Some(rule) = self.check_step(report[i], report[i + 1], direction) {
+                violations.push(Violation { index: i, first_level: report[i], second_level: report[i + 1], rule });
+            }
         }
+        return violations;
+    }
+
+    /// Why the report is safe or not, see `Verdict`.
+    pub fn explain(&self, report: &[i32]) -> Verdict {
+        let direction = self.find_intended_direction(report);
+        let violations = self.find_violations(report, direction);
+        if violations.is_empty() {
+            return Verdict::Safe;
+        }
+
+        let levels_to_remove = self.find_levels_to_remove(report, direction);
+        if levels_to_remove.len() > self.tolerance {
+            return Verdict::Unsafe { violation: violations[0] };
+        }
+
+        let removed = levels_to_remove.iter().map(|index| RemovedLevel {
+            index: *index,
+            level: report[*index],
+            fixes: violations.iter().find(|violation| violation.index == *index || violation.index + 1 == *index).copied()
+        }).collect();
+        return Verdict::SafeAfterRemoving { removed };
     }
-    return false;
 }
 
-fn is_report_safe_strict(report: &Vec<i32>, asc: bool) -> bool {
-    for i in 0..report.len() - 1 {
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    DirectionChange,
+    StepTooSmall,
+    StepTooLarge
+}
 
-        let first_number = report[i];
-        let second_number = report[i+1];
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Rule::DirectionChange => write!(f, "direction change"),
+            Rule::StepTooSmall => write!(f, "step too small"),
+            Rule::StepTooLarge => write!(f, "step too large")
+        };
+    }
+}
 
-        if asc && first_number > second_number {
-            return false;
-        }
-        else if !asc && second_number > first_number {
-            return false;
-        }
+/// The step from level `index` to level `index + 1` breaks `rule`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Violation {
+    pub index: usize,
+    pub first_level: i32,
+    pub second_level: i32,
+    pub rule: Rule
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{} from {} to {} (levels {} and {})", self.rule, self.first_level, self.second_level, self.index, self.index + 1);
+    }
+}
+
+/// `fixes` is the first violation involving the removed level.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RemovedLevel {
+    pub index: usize,
+    pub level: i32,
+    pub fixes: Option<Violation>
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Verdict {
+    Safe,
+    SafeAfterRemoving { removed: Vec<RemovedLevel> },
+    /// `violation` is the first step breaking a rule, in the direction the report was meant to go.
+    Unsafe { violation: Violation }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Verdict::Safe => write!(f, "safe"),
+            Verdict::Unsafe { violation } => write!(f, "unsafe: {}", violation),
+            Verdict::SafeAfterRemoving { removed } => {
+                write!(f, "safe after removing")?;
+                for (i, removed_level) in removed.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "," };
+                    write!(f, "{} level {} ({})", separator, removed_level.index, removed_level.level)?;
+                    if let Some(violation) = removed_level.fixes {
+                        write!(f, ", which fixes the {}", violation)?;
+                    }
+                }
+                Ok(())
+            }
+        };
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ReportExplanation {
+    pub line: usize,
+    pub levels: Vec<i32>,
+    pub verdict: Verdict
+}
+
+pub fn explain_reports_from_reader<R: BufRead>(reader: R, validator: &ReportValidator) -> io::Result<Vec<ReportExplanation>> {
 
-        let mut difference = second_number - first_number;
-        difference = difference.abs();
+    let mut explanations = Vec::new();
 
-        if difference < 1 || difference > 3 {
-            return false;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
         }
+        let levels = read_line_of_strings(&line);
+        let verdict = validator.explain(&levels);
+        explanations.push(ReportExplanation { line: index + 1, levels, verdict });
     }
-    println!("Some report works");
-    return true;
+
+    return Ok(explanations);
+}
+
+/// One line per report, its levels aligned in a column and followed by the verdict.
+pub fn format_explanations(explanations: &[ReportExplanation]) -> String {
+
+    let levels: Vec<String> = explanations.iter()
+        .map(|explanation| explanation.levels.iter().map(|level| level.to_string()).collect::<Vec<String>>().join(" "))
+        .collect();
+    let width = levels.iter().map(|text| text.len()).max().unwrap_or(0);
+
+    let mut listing = String::new();
+    for (explanation, levels) in explanations.iter().zip(levels) {
+        listing += &format!("{:>5}  {:<width$}  {}\n", explanation.line, levels, explanation.verdict);
+    }
+
+    let safe_count = explanations.iter().filter(|explanation| !matches!(explanation.verdict, Verdict::Unsafe { .. })).count();
+    listing += &format!("\n{} of {} reports are safe", safe_count, explanations.len());
+    return listing;
+}
+
+pub fn explanations_to_json(explanations: &[ReportExplanation]) -> String {
+
+    return serde_json::to_string_pretty(explanations).unwrap();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    //#[test]
+    #[test]
     fn given_safe_with_bad_level_1() {
         let report = vec![1, 2, 3, 4, 4];
 
@@ -160,7 +386,7 @@ mod tests {
         assert_eq!(true, result);
     }
 
-    //#[test]
+    #[test]
     fn given_safe_with_bad_level_2() {
         let report = vec![1, 4, 7, 7, 10];
 
@@ -169,7 +395,7 @@ mod tests {
         assert_eq!(true, result);
     }
 
-    //#[test]
+    #[test]
     fn given_safe_with_bad_level_3() {
         let report = vec![1, 4, 7, 5, 10];
 
@@ -178,7 +404,7 @@ mod tests {
         assert_eq!(true, result);
     }
 
-    //#[test]
+    #[test]
     fn given_safe_with_bad_level_4() {
         let report = vec![1, 3, 2, 4, 5];
 
@@ -187,7 +413,7 @@ mod tests {
         assert_eq!(true, result);
     }
 
-    //#[test]
+    #[test]
     fn given_safe_with_bad_level_5() {
         let report = vec![1, 3, 2, 6, 8];
 
@@ -196,7 +422,7 @@ mod tests {
         assert_eq!(true, result);
     }
 
-    //#[test]
+    #[test]
     fn given_safe_with_bad_level_6() {
         let report = vec![1, 3, 2, 3, 6];
 
@@ -205,7 +431,7 @@ mod tests {
         assert_eq!(true, result);
     }
 
-    //#[test]
+    #[test]
     fn given_safe_with_bad_level_7() {
         let report = vec![10, 1, 2, 3, 4];
 
@@ -214,7 +440,7 @@ mod tests {
         assert_eq!(true, result);
     }
 
-    //#[test]
+    #[test]
     fn given_safe_with_bad_level_8() {
         let report = vec![1, 6, 4, 2, 1];
 
@@ -232,7 +458,7 @@ mod tests {
         assert_eq!(true, result);
     }
 
-    //#[test]
+    #[test]
     fn given_not_safe_with_two_bad_levels1() {
         let report = vec![59, 61, 63, 66, 64, 66, 66];
 
@@ -241,7 +467,7 @@ mod tests {
         assert_eq!(false, result);
     }
 
-    //#[test]
+    #[test]
     fn given_a_safe_ascending_report_if_safe_then_report_is_safe() {
         let report = vec![1, 3, 6, 7, 9];
 
@@ -250,7 +476,7 @@ mod tests {
         assert_eq!(true, result);
     }
 
-    //#[test]
+    #[test]
     fn given_a_report_with_one_element_if_safe_then_report_is_safe() {
         let report = vec![6];
 
@@ -259,7 +485,7 @@ mod tests {
         assert_eq!(true, result);
     }
 
-    //#[test]
+    #[test]
     fn given_a_line_reading_line_should_return_numbers_in_vec() {
         let input = "7 6 4 2 1";
 
@@ -268,7 +494,7 @@ mod tests {
         assert_eq!(result, [7, 6, 4, 2, 1]);
     }
 
-    //#[test]
+    #[test]
     fn given_two_lines_report_list_is_created() {
         let input = "7 6
 1 2";
@@ -278,7 +504,7 @@ mod tests {
         assert_eq!(result, [[7, 6], [1, 2]]);
     }
 
-    //#[test]
+    #[test]
     fn given_example_two_reports_are_safe() {
         let input = "7 6 4 2 1
 1 2 7 8 9
@@ -291,4 +517,96 @@ mod tests {
         
         assert_eq!(result, 4);
     }
+
+    #[test]
+    fn given_lines_from_reader_safe_reports_are_counted() {
+        let input = "7 6 4 2 1
+1 2 7 8 9
+";
+
+        let result = get_amount_of_safe_reports_from_reader(input.as_bytes()).unwrap();
+
+        assert_eq!(result, 1);
+    }
+
+    #[test]
+    fn given_strict_rules_a_bad_level_is_not_tolerated() {
+        let report = vec![1, 3, 2, 4, 5];
+
+        assert_eq!(STRICT_RULES.count_levels_to_remove(&report), None);
+        assert_eq!(PROBLEM_DAMPENER.count_levels_to_remove(&report), Some(1));
+    }
+
+    #[test]
+    fn given_tolerance_of_two_two_bad_levels_are_removed() {
+        let validator = ReportValidator { tolerance: 2, ..STRICT_RULES };
+        let report = vec![59, 61, 63, 66, 64, 66, 66];
+
+        let result = validator.count_levels_to_remove(&report);
+
+        assert_eq!(result, Some(2));
+    }
+
+    #[test]
+    fn given_bad_first_levels_they_can_be_removed() {
+        let validator = ReportValidator { tolerance: 2, ..STRICT_RULES };
+        let report = vec![50, 1, 2, 3, 4];
+
+        assert_eq!(validator.count_levels_to_remove(&report), Some(1));
+        assert_eq!(validator.count_levels_to_remove(&[50, 40, 1, 2, 3]), Some(2));
+    }
+
+    #[test]
+    fn given_required_direction_other_direction_is_unsafe() {
+        let validator = ReportValidator { min_step: 1, max_step: 5, monotonicity: Monotonicity::Increasing, tolerance: 1 };
+
+        assert!(!validator.is_safe(&[9, 7, 6, 2, 1]));
+        assert!(!validator.is_safe(&[1, 2, 8, 9]));
+        assert!(validator.is_safe(&[1, 2, 1, 6]));
+    }
+
+    #[test]
+    fn given_safe_report_verdict_is_safe() {
+        let result = PROBLEM_DAMPENER.explain(&[7, 6, 4, 2, 1]);
+
+        assert_eq!(result, Verdict::Safe);
+    }
+
+    #[test]
+    fn given_one_bad_level_verdict_names_removed_level() {
+        let result = PROBLEM_DAMPENER.explain(&[1, 3, 2, 4, 5]);
+
+        let violation = Violation { index: 1, first_level: 3, second_level: 2, rule: Rule::DirectionChange };
+        assert_eq!(result, Verdict::SafeAfterRemoving { removed: vec![RemovedLevel { index: 2, level: 2, fixes: Some(violation) }] });
+    }
+
+    #[test]
+    fn given_unsafe_report_verdict_names_first_violation() {
+        let result = PROBLEM_DAMPENER.explain(&[1, 2, 7, 8, 9]);
+
+        assert_eq!(result, Verdict::Unsafe { violation: Violation { index: 1, first_level: 2, second_level: 7, rule: Rule::StepTooLarge } });
+    }
+
+    #[test]
+    fn given_flat_step_rule_is_step_too_small() {
+        let result = STRICT_RULES.explain(&[8, 6, 4, 4, 1]);
+
+        assert_eq!(result, Verdict::Unsafe { violation: Violation { index: 2, first_level: 4, second_level: 4, rule: Rule::StepTooSmall } });
+    }
+
+    #[test]
+    fn given_example_listing_shows_every_verdict() {
+        let input = "7 6 4 2 1
+1 2 7 8 9
+8 6 4 4 1";
+
+        let explanations = explain_reports_from_reader(input.as_bytes(), &PROBLEM_DAMPENER).unwrap();
+        let result = format_explanations(&explanations);
+
+        assert_eq!(result, "    1  7 6 4 2 1  safe
+    2  1 2 7 8 9  unsafe: step too large from 2 to 7 (levels 1 and 2)
+    3  8 6 4 4 1  safe after removing level 3 (4), which fixes the step too small from 4 to 4 (levels 2 and 3)
+
+2 of 3 reports are safe");
+    }
 }
@@ -0,0 +1,535 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::Path;
+
+use crate::{read_line_of_strings, Monotonicity, ReportValidator};
+
+/// A constraint on a whole report, built in code or read from a rule file with `parse_rules`.
+///
+/// The rule file has one rule per line, all of which must hold. `#` starts a comment.
+///
+/// ```text
+/// step 1..3                      # every step is between 1 and 3 in absolute value
+/// monotonic either strict        # increasing, decreasing or either; strict or non-strict
+/// plateau 2                      # at most 2 equal levels in a row
+/// any(step 1..1, step 5..5)      # at least one of the rules holds
+/// all(plateau 1, step 0..10)     # every one of the rules holds
+/// ```
+///
+/// An empty `all()` always holds and an empty `any()` never does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SequenceRule {
+    /// Every difference between consecutive levels is within `min..=max` in absolute value.
+    StepRange { min: i32, max: i32 },
+    /// Non-strict monotonicity allows equal consecutive levels.
+    Monotonic { direction: Monotonicity, strict: bool },
+    /// The longest run of equal consecutive levels.
+    MaxPlateau { length: usize },
+    All(Vec<SequenceRule>),
+    Any(Vec<SequenceRule>)
+}
+
+impl SequenceRule {
+
+    /// The puzzle's rules, without the Problem Dampener.
+    pub fn puzzle_rules() -> SequenceRule {
+        return SequenceRule::from_validator(&crate::STRICT_RULES);
+    }
+
+    /// The rules a validator checks, its `tolerance` is given to `is_satisfied_with_tolerance` instead.
+    pub fn from_validator(validator: &ReportValidator) -> SequenceRule {
+        return SequenceRule::All(vec![
+            SequenceRule::Monotonic { direction: validator.monotonicity, strict: true },
+            SequenceRule::StepRange { min: validator.min_step, max: validator.max_step }
+        ]);
+    }
+
+    pub fn is_satisfied(&self, report: &[i32]) -> bool {
+        return match self {
+            SequenceRule::StepRange { min, max } => report.windows(2)
+                .all(|pair| (pair[1] - pair[0]).abs() >= *min && (pair[1] - pair[0]).abs() <= *max),
+            SequenceRule::Monotonic { direction: Monotonicity::Either, strict } =>
+                is_monotonic(report, Monotonicity::Increasing, *strict) || is_monotonic(report, Monotonicity::Decreasing, *strict),
+            SequenceRule::Monotonic { direction, strict } => is_monotonic(report, *direction, *strict),
+            SequenceRule::MaxPlateau { length } => find_longest_plateau(report) <= *length,
+            SequenceRule::All(rules) => rules.iter().all(|rule| rule.is_satisfied(report)),
+            SequenceRule::Any(rules) => rules.iter().any(|rule| rule.is_satisfied(report))
+        };
+    }
+
+    /// Whether the rule holds once at most `tolerance` levels are removed, like the Problem Dampener.
+    /// The rule is split into alternatives that each only look at consecutive levels and plateaus,
+    /// which are checked the way `ReportValidator` does, in O(n * tolerance) per alternative and allowed plateau length.
+    pub fn is_satisfied_with_tolerance(&self, report: &[i32], tolerance: usize) -> bool {
+        return self.find_alternatives().iter().any(|alternative| alternative.count_levels_to_remove(report, tolerance) <= tolerance);
+    }
+
+    /// The rule as a list of alternatives, at least one of which must hold. `Monotonic` in either direction is two of them.
+    fn find_alternatives(&self) -> Vec<StepConstraints> {
+        return match self {
+            SequenceRule::StepRange { min, max } => vec![StepConstraints { step_ranges: vec![(*min, *max)], ..StepConstraints::none() }],
+            SequenceRule::Monotonic { direction: Monotonicity::Either, strict } => vec![
+                StepConstraints { directions: vec![(Monotonicity::Increasing, *strict)], ..StepConstraints::none() },
+                StepConstraints { directions: vec![(Monotonicity::Decreasing, *strict)], ..StepConstraints::none() }
+            ],
+            SequenceRule::Monotonic { direction, strict } => vec![StepConstraints { directions: vec![(*direction, *strict)], ..StepConstraints::none() }],
+            SequenceRule::MaxPlateau { length } => vec![StepConstraints { max_plateau: *length, ..StepConstraints::none() }],
+            SequenceRule::Any(rules) => rules.iter().flat_map(|rule| rule.find_alternatives()).collect(),
+            SequenceRule::All(rules) => {
+                let mut alternatives = vec![StepConstraints::none()];
+                for rule in rules {
+                    let others = rule.find_alternatives();
+                    let mut combined = Vec::new();
+                    for alternative in &alternatives {
+                        for other in &others {
+                            combined.push(alternative.combine(other));
+                        }
+                    }
+                    alternatives = combined;
+                }
+                alternatives
+            }
+        };
+    }
+
+    pub fn count_satisfying_reports(&self, reports: &[Vec<i32>]) -> usize {
+        return reports.iter().filter(|report| self.is_satisfied(report)).count();
+    }
+}
+
+/// Rules that only look at two consecutive levels at a time, plus the longest run of equal levels, all of which must hold.
+#[derive(Clone, Debug)]
+struct StepConstraints {
+    step_ranges: Vec<(i32, i32)>,
+    directions: Vec<(Monotonicity, bool)>,
+    max_plateau: usize
+}
+
+impl StepConstraints {
+
+    fn none() -> StepConstraints {
+        return StepConstraints { step_ranges: Vec::new(), directions: Vec::new(), max_plateau: usize::MAX };
+    }
+
+    fn combine(&self, other: &StepConstraints) -> StepConstraints {
+        return StepConstraints {
+            step_ranges: [self.step_ranges.as_slice(), other.step_ranges.as_slice()].concat(),
+            directions: [self.directions.as_slice(), other.directions.as_slice()].concat(),
+            max_plateau: self.max_plateau.min(other.max_plateau)
+        };
+    }
+
+    fn is_step_allowed(&self, first_level: i32, second_level: i32) -> bool {
+        let step = (second_level - first_level).abs();
+        return self.step_ranges.iter().all(|(min, max)| step >= *min && step <= *max)
+            && self.directions.iter().all(|(direction, strict)| is_monotonic(&[first_level, second_level], *direction, *strict));
+    }
+
+    /// Same as `ReportValidator::count_levels_to_remove`, with the length of the plateau ending at each kept level
+    /// as part of the state: `removals[i][p]` is the fewest removals among the first i levels when level i is kept
+    /// as the last of `p + 1` equal levels. Plateaus as long as the report are always allowed, so they are not tracked.
+    /// Only exact when the result is within `tolerance`.
+    fn count_levels_to_remove(&self, report: &[i32], tolerance: usize) -> usize {
+        let tracks_plateaus = self.max_plateau < report.len();
+        let plateau_lengths = if tracks_plateaus { self.max_plateau } else { 1 };
+        let mut removals: Vec<Vec<Option<usize>>> = Vec::new();
+        let mut fewest_removals = report.len();
+        for i in 0..report.len() {
+            let mut removals_keeping_i = vec![None; plateau_lengths];
+            if plateau_lengths > 0 {
+                removals_keeping_i[0] = Some(i);
+            }
+            for j in i.saturating_sub(tolerance + 1)..i {
+                if !self.is_step_allowed(report[j], report[i]) {
+                    continue;
+                }
+                for (p, removals_keeping_j) in removals[j].iter().enumerate() {
+                    let plateau = if tracks_plateaus && report[j] == report[i] { p + 1 } else { 0 };
+                    let removals_keeping_j = match removals_keeping_j {
+                        Some(removals_keeping_j) if plateau < plateau_lengths => *removals_keeping_j,
+                        _ => continue
+                    };
+                    let candidate = removals_keeping_j + i - j - 1;
+                    if removals_keeping_i[plateau].is_none_or(|current| candidate < current) {
+                        removals_keeping_i[plateau] = Some(candidate);
+                    }
+                }
+            }
+            for removals_before in removals_keeping_i.iter().flatten() {
+                fewest_removals = fewest_removals.min(removals_before + report.len() - 1 - i);
+            }
+            removals.push(removals_keeping_i);
+        }
+        return fewest_removals;
+    }
+}
+
+/// Like `count_safe_reports_from_reader`, with the rule checked once per tolerance.
+/// Returns the amount of reports satisfying the rule for each tolerance, in the same order.
+pub fn count_satisfying_reports_from_reader<R: BufRead>(reader: R, rule: &SequenceRule, tolerances: &[usize]) -> io::Result<Vec<i32>> {
+
+    let mut amounts_of_satisfying_reports = vec![0; tolerances.len()];
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let report = read_line_of_strings(&line);
+        for (i, tolerance) in tolerances.iter().enumerate() {
+            if rule.is_satisfied_with_tolerance(&report, *tolerance) {
+                amounts_of_satisfying_reports[i] += 1;
+            }
+        }
+    }
+
+    return Ok(amounts_of_satisfying_reports);
+}
+
+fn is_monotonic(report: &[i32], direction: Monotonicity, strict: bool) -> bool {
+    return report.windows(2).all(|pair| match (direction, strict) {
+        (Monotonicity::Increasing, true) => pair[1] > pair[0],
+        (Monotonicity::Increasing, false) => pair[1] >= pair[0],
+        (Monotonicity::Decreasing, true) => pair[1] < pair[0],
+        (Monotonicity::Decreasing, false) => pair[1] <= pair[0],
+        (Monotonicity::Either, _) => true
+    });
+}
+
+/// An empty report has no plateau, any other has at least one level in a row.
+fn find_longest_plateau(report: &[i32]) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for i in 0..report.len() {
+        if i > 0 && report[i] == report[i - 1] {
+            current += 1;
+        } else {
+            current = 1;
+        }
+        longest = longest.max(current);
+    }
+    return longest;
+}
+
+impl fmt::Display for SequenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            SequenceRule::StepRange { min, max } => write!(f, "step {}..{}", min, max),
+            SequenceRule::Monotonic { direction, strict } => {
+                let direction = match direction {
+                    Monotonicity::Increasing => "increasing",
+                    Monotonicity::Decreasing => "decreasing",
+                    Monotonicity::Either => "either"
+                };
+                write!(f, "monotonic {} {}", direction, if *strict { "strict" } else { "non-strict" })
+            }
+            SequenceRule::MaxPlateau { length } => write!(f, "plateau {}", length),
+            SequenceRule::All(rules) => write!(f, "all({})", join_rules(rules)),
+            SequenceRule::Any(rules) => write!(f, "any({})", join_rules(rules))
+        };
+    }
+}
+
+fn join_rules(rules: &[SequenceRule]) -> String {
+    return rules.iter().map(|rule| rule.to_string()).collect::<Vec<String>>().join(", ");
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RuleParseError {
+    pub line: usize,
+    pub message: String
+}
+
+impl fmt::Display for RuleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "Line {}: {}", self.line, self.message);
+    }
+}
+
+impl std::error::Error for RuleParseError {}
+
+/// Reads a rule file, see `SequenceRule` for the syntax. The rules of every line are combined with `all`.
+pub fn parse_rules(text: &str) -> Result<SequenceRule, RuleParseError> {
+    let mut rules = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = match line.find('#') {
+            Some(comment_start) => &line[..comment_start],
+            None => line
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut parser = RuleParser { tokens: tokenize(line), position: 0 };
+        let rule = parser.parse_rule().and_then(|rule| match parser.peek() {
+            None => Ok(rule),
+            Some(token) => Err(format!("Unexpected {}", token))
+        });
+        match rule {
+            Ok(rule) => rules.push(rule),
+            Err(message) => return Err(RuleParseError { line: index + 1, message })
+        }
+    }
+
+    return Ok(SequenceRule::All(rules));
+}
+
+pub fn load_rules(path: &Path) -> Result<SequenceRule, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+    return parse_rules(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e));
+}
+
+/// Words, numbers (with their sign) and the punctuation `(`, `)`, `,` and `..`.
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let characters: Vec<char> = line.chars().collect();
+
+    let mut i = 0;
+    while i < characters.len() {
+        let character = characters[i];
+        let is_range = character == '.' && characters.get(i + 1) == Some(&'.');
+        if character.is_whitespace() || "(),".contains(character) || is_range {
+            if !current.is_empty() {
+                tokens.push(current.clone());
+                current.clear();
+            }
+            if is_range {
+                tokens.push(String::from(".."));
+                i += 1;
+            } else if !character.is_whitespace() {
+                tokens.push(character.to_string());
+            }
+        } else {
+            current.push(character);
+        }
+        i += 1;
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    return tokens;
+}
+
+struct RuleParser {
+    tokens: Vec<String>,
+    position: usize
+}
+
+impl RuleParser {
+
+    fn peek(&self) -> Option<&str> {
+        return self.tokens.get(self.position).map(String::as_str);
+    }
+
+    fn next(&mut self) -> Result<String, String> {
+        let token = self.tokens.get(self.position).cloned().ok_or(String::from("Unexpected end of rule"))?;
+        self.position += 1;
+        return Ok(token);
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        let token = self.next()?;
+        if token != expected {
+            return Err(format!("Expected {}, found {}", expected, token));
+        }
+        return Ok(());
+    }
+
+    fn parse_number<T: std::str::FromStr>(&mut self) -> Result<T, String> {
+        let token = self.next()?;
+        return token.parse().map_err(|_| format!("Expected a number, found {}", token));
+    }
+
+    fn parse_rule(&mut self) -> Result<SequenceRule, String> {
+        let keyword = self.next()?;
+        return match keyword.as_str() {
+            "step" => {
+                let min = self.parse_number()?;
+                self.expect("..")?;
+                let max = self.parse_number()?;
+                Ok(SequenceRule::StepRange { min, max })
+            }
+            "monotonic" => {
+                let direction = match self.next()?.as_str() {
+                    "increasing" => Monotonicity::Increasing,
+                    "decreasing" => Monotonicity::Decreasing,
+                    "either" => Monotonicity::Either,
+                    other => return Err(format!("Expected increasing, decreasing or either, found {}", other))
+                };
+                let strict = match self.peek() {
+                    Some("strict") => true,
+                    Some("non-strict") => false,
+                    _ => return Ok(SequenceRule::Monotonic { direction, strict: true })
+                };
+                self.position += 1;
+                Ok(SequenceRule::Monotonic { direction, strict })
+            }
+            "plateau" => Ok(SequenceRule::MaxPlateau { length: self.parse_number()? }),
+            "all" => Ok(SequenceRule::All(self.parse_rule_list()?)),
+            "any" => Ok(SequenceRule::Any(self.parse_rule_list()?)),
+            other => Err(format!("Unknown rule {}", other))
+        };
+    }
+
+    fn parse_rule_list(&mut self) -> Result<Vec<SequenceRule>, String> {
+        self.expect("(")?;
+        if self.peek() == Some(")") {
+            self.position += 1;
+            return Ok(Vec::new());
+        }
+        let mut rules = vec![self.parse_rule()?];
+        loop {
+            match self.next()?.as_str() {
+                "," => rules.push(self.parse_rule()?),
+                ")" => return Ok(rules),
+                other => return Err(format!("Expected , or ), found {}", other))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_reports;
+
+    #[test]
+    fn given_puzzle_rules_example_has_two_safe_reports() {
+        let reports = create_reports("7 6 4 2 1
+1 2 7 8 9
+9 7 6 2 1
+1 3 2 4 5
+8 6 4 4 1
+1 3 6 7 9");
+
+        let result = SequenceRule::puzzle_rules().count_satisfying_reports(&reports);
+
+        assert_eq!(result, 2);
+    }
+
+    #[test]
+    fn given_non_strict_monotonicity_plateaus_are_limited_separately() {
+        let rule = SequenceRule::All(vec![
+            SequenceRule::Monotonic { direction: Monotonicity::Increasing, strict: false },
+            SequenceRule::MaxPlateau { length: 2 }
+        ]);
+
+        assert!(rule.is_satisfied(&[1, 1, 2, 3, 3]));
+        assert!(!rule.is_satisfied(&[1, 1, 1, 2]));
+        assert!(!rule.is_satisfied(&[3, 2, 2]));
+    }
+
+    #[test]
+    fn given_rule_file_rules_are_parsed() {
+        let text = "# the puzzle, or tiny steps
+any(all(monotonic either, step 1..3), step 0..1)
+plateau 3";
+
+        let result = parse_rules(text).unwrap();
+
+        assert_eq!(result, SequenceRule::All(vec![
+            SequenceRule::Any(vec![
+                SequenceRule::All(vec![
+                    SequenceRule::Monotonic { direction: Monotonicity::Either, strict: true },
+                    SequenceRule::StepRange { min: 1, max: 3 }
+                ]),
+                SequenceRule::StepRange { min: 0, max: 1 }
+            ]),
+            SequenceRule::MaxPlateau { length: 3 }
+        ]));
+    }
+
+    #[test]
+    fn given_rule_displayed_it_parses_back() {
+        let rule = parse_rules("any(monotonic decreasing non-strict, plateau 1)").unwrap();
+
+        assert_eq!(parse_rules(&rule.to_string()).unwrap(), SequenceRule::All(vec![rule]));
+    }
+
+    #[test]
+    fn given_empty_combinations_they_parse_back() {
+        let rule = SequenceRule::Any(vec![SequenceRule::All(vec![]), SequenceRule::Any(vec![])]);
+
+        let result = parse_rules(&rule.to_string()).unwrap();
+
+        assert_eq!(result, SequenceRule::All(vec![rule]));
+        assert!(SequenceRule::All(vec![]).is_satisfied(&[1, 9]));
+        assert!(!SequenceRule::Any(vec![]).is_satisfied(&[1, 2]));
+    }
+
+    #[test]
+    fn given_example_rules_with_tolerance_match_the_validators() {
+        let input = "7 6 4 2 1
+1 2 7 8 9
+9 7 6 2 1
+1 3 2 4 5
+8 6 4 4 1
+1 3 6 7 9";
+
+        let result = count_satisfying_reports_from_reader(input.as_bytes(), &SequenceRule::puzzle_rules(), &[0, 1]).unwrap();
+
+        assert_eq!(result, [2, 4]);
+        assert_eq!(result, crate::count_safe_reports_from_reader(input.as_bytes(), &[crate::STRICT_RULES, crate::PROBLEM_DAMPENER]).unwrap());
+    }
+
+    /// Every way of removing up to `tolerance` levels.
+    fn is_satisfied_removing_any(rule: &SequenceRule, report: &[i32], tolerance: usize) -> bool {
+        if rule.is_satisfied(report) {
+            return true;
+        }
+        if tolerance == 0 {
+            return false;
+        }
+        for i in 0..report.len() {
+            let mut shorter_report = report.to_vec();
+            shorter_report.remove(i);
+            if is_satisfied_removing_any(rule, &shorter_report, tolerance - 1) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    #[test]
+    fn given_every_short_report_tolerance_matches_trying_every_removal() {
+        let rules = [
+            SequenceRule::puzzle_rules(),
+            parse_rules("any(all(monotonic either non-strict, plateau 2), step 2..2)").unwrap(),
+            parse_rules("step 0..1\nplateau 1").unwrap(),
+            parse_rules("monotonic decreasing strict\nany()").unwrap()
+        ];
+
+        for code in 0..4usize.pow(6) {
+            let report: Vec<i32> = (0..6).map(|digit| (code / 4usize.pow(digit) % 4) as i32).collect();
+            for rule in &rules {
+                for tolerance in 0..=2 {
+                    assert_eq!(rule.is_satisfied_with_tolerance(&report, tolerance), is_satisfied_removing_any(rule, &report, tolerance),
+                        "{} on {:?} with tolerance {}", rule, report, tolerance);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn given_long_report_large_tolerance_is_checked_quickly() {
+        let mut report: Vec<i32> = (0..2000).collect();
+        for i in (0..report.len()).step_by(200) {
+            report[i] = -50;
+        }
+
+        assert!(SequenceRule::puzzle_rules().is_satisfied_with_tolerance(&report, 10));
+        assert!(!SequenceRule::puzzle_rules().is_satisfied_with_tolerance(&report, 9));
+    }
+
+    #[test]
+    fn given_bad_rule_error_has_line() {
+        let result = parse_rules("step 1..3\nstep 1 3");
+
+        assert_eq!(result, Err(RuleParseError { line: 2, message: String::from("Expected .., found 3") }));
+    }
+}
@@ -0,0 +1,231 @@
+use serde::Serialize;
+
+use crate::{tokenize, Interpreter, Span, Token};
+
+/// One instruction of the memory, with whether multiplications were enabled when it ran
+/// and what it added to the part 2 sum.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DisassembledInstruction {
+    pub token: Token,
+    pub span: Span,
+    pub text: String,
+    pub is_active: bool,
+    pub contribution: i128
+}
+
+/// Runs the tokens with an `Interpreter` handling conditionals, recording its state before each one.
+pub fn disassemble<T: AsRef<[u8]> + ?Sized>(input: &T) -> Vec<DisassembledInstruction> {
+    let input = input.as_ref();
+    let mut interpreter = Interpreter::new(true);
+    let mut instructions = Vec::new();
+
+    for spanned_token in tokenize(input) {
+        let is_active = interpreter.is_multiplying_active;
+        let sum_before = interpreter.sum;
+        interpreter.execute(&spanned_token.token);
+
+        instructions.push(DisassembledInstruction {
+            token: spanned_token.token,
+            span: spanned_token.span,
+            text: text_between(input, spanned_token.span.start, spanned_token.span.end),
+            is_active,
+            contribution: interpreter.sum - sum_before
+        });
+    }
+
+    return instructions;
+}
+
+/// Instructions are ASCII, only the memory around them can have bytes that are not valid UTF-8, which are replaced.
+fn text_between(input: &[u8], start: usize, end: usize) -> String {
+    return String::from_utf8_lossy(&input[start..end]).into_owned();
+}
+
+/// One line per instruction: byte offset, instruction, state it ran in and contribution, then the sum.
+pub fn format_disassembly(instructions: &[DisassembledInstruction]) -> String {
+    let offset_width = instructions.iter().map(|instruction| instruction.span.start.to_string().len()).max().unwrap_or(0).max(6);
+    let text_width = instructions.iter().map(|instruction| instruction.text.len()).max().unwrap_or(0).max(11);
+
+    let mut listing = format!("{:>offset_width$}  {:<text_width$}  {:<8}  {}\n", "Offset", "Instruction", "State", "Contribution");
+    for instruction in instructions {
+        let state = if instruction.is_active { "enabled" } else { "disabled" };
+        let contribution = match instruction.token {
+            Token::Mul(_, _) => instruction.contribution.to_string(),
+            _ => String::new()
+        };
+        let line = format!("{:>offset_width$}  {:<text_width$}  {:<8}  {}", instruction.span.start, instruction.text, state, contribution);
+        listing += line.trim_end();
+        listing += "\n";
+    }
+
+    let sum: i128 = instructions.iter().map(|instruction| instruction.contribution).sum();
+    listing += &format!("\n{} instructions, sum {}", instructions.len(), sum);
+    return listing;
+}
+
+pub fn disassembly_to_json(instructions: &[DisassembledInstruction]) -> String {
+
+    return serde_json::to_string_pretty(instructions).unwrap();
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Highlight {
+    EnabledMul,
+    DisabledMul,
+    Conditional
+}
+
+fn classify(instruction: &DisassembledInstruction) -> Highlight {
+    return match (instruction.token, instruction.is_active) {
+        (Token::Mul(_, _), true) => Highlight::EnabledMul,
+        (Token::Mul(_, _), false) => Highlight::DisabledMul,
+        _ => Highlight::Conditional
+    };
+}
+
+/// The memory as it is, with enabled multiplications in green, disabled ones in red
+/// and `do()`/`don't()` in yellow. Everything else is dimmed.
+pub fn highlight_ansi<T: AsRef<[u8]> + ?Sized>(input: &T, instructions: &[DisassembledInstruction]) -> String {
+    const RESET: &str = "\x1b[0m";
+    const DIM: &str = "\x1b[2m";
+
+    let input = input.as_ref();
+    let mut output = String::new();
+    let mut position = 0;
+    for instruction in instructions {
+        if instruction.span.start > position {
+            output += &format!("{}{}{}", DIM, text_between(input, position, instruction.span.start), RESET);
+        }
+        let colour = match classify(instruction) {
+            Highlight::EnabledMul => "\x1b[1;32m",
+            Highlight::DisabledMul => "\x1b[31m",
+            Highlight::Conditional => "\x1b[1;33m"
+        };
+        output += &format!("{}{}{}", colour, instruction.text, RESET);
+        position = instruction.span.end;
+    }
+    if position < input.len() {
+        output += &format!("{}{}{}", DIM, text_between(input, position, input.len()), RESET);
+    }
+
+    return output;
+}
+
+/// A standalone page with the same colours as `highlight_ansi`. Each instruction has its contribution as a tooltip.
+pub fn highlight_html<T: AsRef<[u8]> + ?Sized>(input: &T, instructions: &[DisassembledInstruction]) -> String {
+    let input = input.as_ref();
+    let mut body = String::new();
+    let mut position = 0;
+    for instruction in instructions {
+        body += &escape_html(&text_between(input, position, instruction.span.start));
+        let class = match classify(instruction) {
+            Highlight::EnabledMul => "mul enabled",
+            Highlight::DisabledMul => "mul disabled",
+            Highlight::Conditional => "conditional"
+        };
+        body += &format!("<span class=\"{}\" title=\"offset {}, contributes {}\">{}</span>",
+            class, instruction.span.start, instruction.contribution, escape_html(&instruction.text));
+        position = instruction.span.end;
+    }
+    body += &escape_html(&text_between(input, position, input.len()));
+
+    return format!("<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Corrupted memory</title>
+<style>
+pre {{ white-space: pre-wrap; word-break: break-all; color: #888; }}
+.mul.enabled {{ color: #080; font-weight: bold; }}
+.mul.disabled {{ color: #c00; }}
+.conditional {{ color: #b80; font-weight: bold; }}
+</style>
+</head>
+<body>
+<pre>{}</pre>
+</body>
+</html>
+", body);
+}
+
+fn escape_html(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn given_example_disassembly_has_states_and_contributions() {
+        let result = disassemble(EXAMPLE);
+
+        let summary: Vec<(usize, &str, bool, i128)> = result.iter()
+            .map(|instruction| (instruction.span.start, instruction.text.as_str(), instruction.is_active, instruction.contribution))
+            .collect();
+        assert_eq!(summary, [
+            (1, "mul(2,4)", true, 8),
+            (20, "don't()", true, 0),
+            (28, "mul(5,5)", false, 0),
+            (48, "mul(11,8)", false, 0),
+            (59, "do()", false, 0),
+            (64, "mul(8,5)", true, 40)
+        ]);
+    }
+
+    #[test]
+    fn given_example_listing_ends_with_sum() {
+        let result = format_disassembly(&disassemble(EXAMPLE));
+
+        assert!(result.contains("    28  mul(5,5)     disabled  0\n"));
+        assert!(result.ends_with("6 instructions, sum 48"));
+    }
+
+    #[test]
+    fn given_large_operands_listing_sum_matches_the_interpreter() {
+        let memory = "mul(999999999,999999999)".repeat(10);
+
+        let instructions = disassemble(&memory);
+        let result = format_disassembly(&instructions);
+
+        assert_eq!(instructions[9].contribution, 999999999i128 * 999999999);
+        assert!(result.ends_with(&format!("10 instructions, sum {}", crate::calculate_result_of_multiplications(&memory))));
+        assert!(highlight_html(&memory, &instructions).contains("contributes 999999998000000001\""));
+    }
+
+    #[test]
+    fn given_example_highlighting_keeps_memory_text() {
+        let instructions = disassemble(EXAMPLE);
+
+        let ansi = highlight_ansi(EXAMPLE, &instructions);
+        let html = highlight_html(EXAMPLE, &instructions);
+
+        let mut stripped = String::new();
+        let mut in_escape = false;
+        for character in ansi.chars() {
+            match character {
+                '\x1b' => in_escape = true,
+                'm' if in_escape => in_escape = false,
+                _ if !in_escape => stripped.push(character),
+                _ => {}
+            }
+        }
+        assert_eq!(stripped, EXAMPLE);
+        assert!(html.contains("<span class=\"mul disabled\" title=\"offset 28, contributes 0\">mul(5,5)</span>"));
+        assert!(html.contains("<span class=\"conditional\" title=\"offset 20, contributes 0\">don't()</span>"));
+    }
+
+    #[test]
+    fn given_invalid_utf8_memory_spans_point_at_the_bytes() {
+        let memory = b"\xffmul(2,3)\xfe<do()";
+
+        let instructions = disassemble(memory);
+        let html = highlight_html(memory, &instructions);
+
+        assert_eq!(instructions[0].span, Span { start: 1, end: 9 });
+        assert_eq!(instructions[1].span, Span { start: 11, end: 15 });
+        assert!(html.contains("<pre>\u{fffd}<span class=\"mul enabled\" title=\"offset 1, contributes 6\">mul(2,3)</span>\u{fffd}&lt;<span"));
+    }
+}
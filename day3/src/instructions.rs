@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{read_number, Span};
+
+/// How deep calls can be nested in each other, deeper calls are not read as instructions.
+pub const MAX_NESTING_DEPTH: usize = 32;
+
+/// The state an instruction can change when it runs at the top level.
+/// The sum is 128 bits, like the one of `Interpreter`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MachineState {
+    pub is_multiplying_active: bool,
+    pub sum: i128
+}
+
+impl MachineState {
+
+    pub fn new() -> MachineState {
+        return MachineState { is_multiplying_active: true, sum: 0 };
+    }
+}
+
+impl Default for MachineState {
+    fn default() -> MachineState {
+        return MachineState::new();
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InstructionError {
+    /// The value of the call does not fit in 64 bits.
+    Overflow(Span),
+    /// The call is nested deeper than `MAX_NESTING_DEPTH`.
+    TooDeep(Span)
+}
+
+impl fmt::Display for InstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            InstructionError::Overflow(span) => write!(f, "The call at offset {} overflows", span.start),
+            InstructionError::TooDeep(span) => write!(f, "The call at offset {} is nested more than {} deep", span.start, MAX_NESTING_DEPTH)
+        };
+    }
+}
+
+impl std::error::Error for InstructionError {}
+
+/// What an instruction means. Arguments are evaluated before `evaluate` is called,
+/// the value it returns is what a call nested in another call stands for, `None` if it overflows.
+#[derive(Clone, Copy)]
+pub struct InstructionDefinition {
+    pub min_arguments: usize,
+    pub max_arguments: usize,
+    pub evaluate: fn(&[i64]) -> Option<i64>,
+    /// What a call that is not nested in another call does with its value.
+    pub effect: fn(&mut MachineState, i64)
+}
+
+/// Adds the value to the sum while multiplications are active, like `mul`.
+pub fn accumulate(state: &mut MachineState, value: i64) {
+    if state.is_multiplying_active {
+        state.sum += value as i128;
+    }
+}
+
+pub fn enable(state: &mut MachineState, _value: i64) {
+    state.is_multiplying_active = true;
+}
+
+pub fn disable(state: &mut MachineState, _value: i64) {
+    state.is_multiplying_active = false;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Argument {
+    Number(i64),
+    Call(Call)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Call {
+    pub name: String,
+    pub arguments: Vec<Argument>,
+    pub span: Span
+}
+
+/// The instructions recognised in the corrupted memory. Every instruction is written `name(argument,argument,...)`
+/// without spaces, where an argument is a number of one to nine digits or, if `allows_nesting`, another call
+/// at most `MAX_NESTING_DEPTH` deep.
+pub struct InstructionSet {
+    instructions: HashMap<String, InstructionDefinition>,
+    pub allows_nesting: bool
+}
+
+impl InstructionSet {
+
+    pub fn new() -> InstructionSet {
+        return InstructionSet { instructions: HashMap::new(), allows_nesting: false };
+    }
+
+    /// Only `mul`, as in part 1 of the puzzle.
+    pub fn multiplications() -> InstructionSet {
+        let mut instruction_set = InstructionSet::new();
+        instruction_set.register("mul", InstructionDefinition { min_arguments: 2, max_arguments: 2, evaluate: |arguments| arguments[0].checked_mul(arguments[1]), effect: accumulate });
+        return instruction_set;
+    }
+
+    /// `mul`, `do` and `don't`, as in part 2 of the puzzle.
+    pub fn puzzle() -> InstructionSet {
+        let mut instruction_set = InstructionSet::multiplications();
+        instruction_set.register("do", InstructionDefinition { min_arguments: 0, max_arguments: 0, evaluate: |_| Some(0), effect: enable });
+        instruction_set.register("don't", InstructionDefinition { min_arguments: 0, max_arguments: 0, evaluate: |_| Some(0), effect: disable });
+        return instruction_set;
+    }
+
+    /// The puzzle instructions plus `add` and `sub`, which also count towards the sum, with nesting allowed.
+    pub fn arithmetic() -> InstructionSet {
+        let mut instruction_set = InstructionSet::puzzle();
+        instruction_set.register("add", InstructionDefinition { min_arguments: 2, max_arguments: 2, evaluate: |arguments| arguments[0].checked_add(arguments[1]), effect: accumulate });
+        instruction_set.register("sub", InstructionDefinition { min_arguments: 2, max_arguments: 2, evaluate: |arguments| arguments[0].checked_sub(arguments[1]), effect: accumulate });
+        instruction_set.allows_nesting = true;
+        return instruction_set;
+    }
+
+    /// Adds an instruction, or replaces the one with the same name.
+    pub fn register(&mut self, name: &str, definition: InstructionDefinition) {
+        self.instructions.insert(String::from(name), definition);
+    }
+
+    /// Finds every top-level call, scanning like `tokenize`: at each byte a call is tried,
+    /// and the scan jumps past it or moves on by one byte.
+    pub fn scan<T: AsRef<[u8]> + ?Sized>(&self, input: &T) -> Vec<Call> {
+        let bytes = input.as_ref();
+        // Longest names first, so that a name which is the start of another never hides it.
+        let mut names: Vec<&String> = self.instructions.keys().collect();
+        names.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+
+        let mut calls = Vec::new();
+        let mut position = 0;
+        while position < bytes.len() {
+            match self.read_call_at(bytes, position, &names, 0) {
+                Some(call) => {
+                    position = call.span.end;
+                    calls.push(call);
+                }
+                None => position += 1
+            }
+        }
+        return calls;
+    }
+
+    /// `depth` is how many calls this one is nested in.
+    fn read_call_at(&self, bytes: &[u8], start: usize, names: &Vec<&String>, depth: usize) -> Option<Call> {
+        if depth > MAX_NESTING_DEPTH {
+            return None;
+        }
+        for name in names {
+            if !bytes[start..].starts_with(name.as_bytes()) || bytes.get(start + name.len()) != Some(&b'(') {
+                continue;
+            }
+            if let Some(call) = self.read_arguments(bytes, start, name, names, depth) {
+                return Some(call);
+            }
+        }
+        return None;
+    }
+
+    fn read_arguments(&self, bytes: &[u8], start: usize, name: &str, names: &Vec<&String>, depth: usize) -> Option<Call> {
+        let definition = &self.instructions[name];
+        let mut arguments = Vec::new();
+        let mut position = start + name.len() + 1;
+
+        if bytes.get(position) != Some(&b')') {
+            loop {
+                let argument = match read_number(bytes, position) {
+                    Some((number, end)) => {
+                        position = end;
+                        Argument::Number(number as i64)
+                    }
+                    None if self.allows_nesting => {
+                        let call = self.read_call_at(bytes, position, names, depth + 1)?;
+                        position = call.span.end;
+                        Argument::Call(call)
+                    }
+                    None => return None
+                };
+                arguments.push(argument);
+
+                match bytes.get(position) {
+                    Some(b',') => position += 1,
+                    Some(b')') => break,
+                    _ => return None
+                }
+            }
+        }
+
+        if arguments.len() < definition.min_arguments || arguments.len() > definition.max_arguments {
+            return None;
+        }
+        return Some(Call { name: String::from(name), arguments, span: Span { start, end: position + 1 } });
+    }
+
+    /// Calls built by hand rather than by `scan` are checked against `MAX_NESTING_DEPTH` too.
+    pub fn evaluate(&self, call: &Call) -> Result<i64, InstructionError> {
+        return self.evaluate_at_depth(call, 0);
+    }
+
+    fn evaluate_at_depth(&self, call: &Call, depth: usize) -> Result<i64, InstructionError> {
+        if depth > MAX_NESTING_DEPTH {
+            return Err(InstructionError::TooDeep(call.span));
+        }
+
+        let mut values = Vec::new();
+        for argument in &call.arguments {
+            values.push(match argument {
+                Argument::Number(number) => *number,
+                Argument::Call(nested_call) => self.evaluate_at_depth(nested_call, depth + 1)?
+            });
+        }
+
+        return (self.instructions[&call.name].evaluate)(&values).ok_or(InstructionError::Overflow(call.span));
+    }
+
+    pub fn execute(&self, state: &mut MachineState, call: &Call) -> Result<(), InstructionError> {
+        let value = self.evaluate(call)?;
+        (self.instructions[&call.name].effect)(state, value);
+        return Ok(());
+    }
+
+    pub fn run<T: AsRef<[u8]> + ?Sized>(&self, input: &T) -> Result<MachineState, InstructionError> {
+        let mut state = MachineState::new();
+        for call in self.scan(input) {
+            self.execute(&mut state, &call)?;
+        }
+        return Ok(state);
+    }
+}
+
+impl Default for InstructionSet {
+    fn default() -> InstructionSet {
+        return InstructionSet::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_puzzle_instructions_example_gives_same_result() {
+        let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+        let result = InstructionSet::puzzle().run(input).unwrap();
+
+        assert_eq!(result.sum, 48);
+    }
+
+    #[test]
+    fn given_puzzle_instructions_nested_calls_are_not_read() {
+        let input = "mul(mul(2,3),4)";
+
+        let result = InstructionSet::puzzle().scan(input);
+
+        assert_eq!(result, [Call { name: String::from("mul"), arguments: vec![Argument::Number(2), Argument::Number(3)], span: Span { start: 4, end: 12 } }]);
+    }
+
+    #[test]
+    fn given_arithmetic_instructions_nested_calls_are_evaluated() {
+        let input = "add(1,2)_mul(sub(10,4),add(1,1))don't()add(100,100)";
+
+        let result = InstructionSet::arithmetic().run(input).unwrap();
+
+        assert_eq!(result.sum, 3 + 12);
+        assert_eq!(result.is_multiplying_active, false);
+    }
+
+    #[test]
+    fn given_registered_instruction_with_three_arguments_it_runs() {
+        let mut instruction_set = InstructionSet::puzzle();
+        instruction_set.register("fma", InstructionDefinition { min_arguments: 3, max_arguments: 3, evaluate: |arguments| Some(arguments[0] * arguments[1] + arguments[2]), effect: accumulate });
+
+        let result = instruction_set.run("fma(2,3,4)fma(1,1)mul(1,1)").unwrap();
+
+        assert_eq!(result.sum, 10 + 1);
+    }
+
+    #[test]
+    fn given_instruction_with_custom_effect_state_changes() {
+        let mut instruction_set = InstructionSet::puzzle();
+        instruction_set.register("reset", InstructionDefinition { min_arguments: 0, max_arguments: 0, evaluate: |_| Some(0), effect: |state, _| state.sum = 0 });
+
+        let result = instruction_set.run("mul(2,3)reset()mul(1,4)").unwrap();
+
+        assert_eq!(result.sum, 4);
+    }
+
+    #[test]
+    fn given_overflowing_nested_calls_running_fails() {
+        let input = "mul(mul(999999999,999999999),999999999)";
+
+        let result = InstructionSet::arithmetic().run(input);
+
+        assert_eq!(result, Err(InstructionError::Overflow(Span { start: 0, end: 39 })));
+    }
+
+    #[test]
+    fn given_calls_nested_too_deep_they_are_not_read() {
+        let nested = |depth: usize| "add(".repeat(depth) + "1" + &",1)".repeat(depth);
+        let instruction_set = InstructionSet::arithmetic();
+
+        let within_limit = instruction_set.run(&nested(MAX_NESTING_DEPTH + 1)).unwrap();
+        let too_deep = instruction_set.scan(&nested(MAX_NESTING_DEPTH + 2));
+
+        assert_eq!(within_limit.sum, MAX_NESTING_DEPTH as i128 + 2);
+        assert_eq!(too_deep[0].span.start, 4);
+    }
+}
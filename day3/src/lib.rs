@@ -1,70 +1,170 @@
-use regex::Regex;
+use std::io::{self, Read};
 
-pub fn mul(left: i32, right: i32) -> i32 {
-    left * right
+use serde::Serialize;
+
+pub mod disassembly;
+pub mod instructions;
+pub mod streaming;
+
+/// Nine-digit operands make products up to about 10^18, which only fit in 64 bits.
+pub fn mul(left: i32, right: i32) -> i64 {
+    left as i64 * right as i64
 }
 
-pub fn find_first_pattern_in_string(input_string: &str) -> Option<&str> {
-    let actual_regex = Regex::new("mul\\([0-9]{1,9},[0-9]{1,9}\\)|do\\(\\)|don't\\(\\)").unwrap();
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Token {
+    Mul(i32, i32),
+    Do,
+    Dont
+}
 
-    let captures = actual_regex.captures(input_string);
+/// Byte offsets of a token in the corrupted memory, `end` excluded.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize
+}
 
-    return match captures {
-        None => None,
-        Some(i) => Some(i.get(0).unwrap().as_str())
-    };
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span
 }
 
-pub fn find_all_patterns(input: &str) -> Vec<&str> {
-    let mut patterns: Vec<&str> = Vec::new();
-    let mut input_copy = input;
-
-    loop {
-        match find_first_pattern_in_string(input_copy) {
-            None => break,
-            Some(pattern) => {
-                patterns.push(pattern);
-                input_copy = &input_copy[input_copy.find(pattern).unwrap()+pattern.len()..];
+/// Reads the memory once from start to end. At each byte an instruction is tried, and the scan either
+/// jumps past the instruction found or moves on by one byte, so anything that is not an instruction is skipped.
+/// The memory can be text or raw bytes, spans are byte offsets either way.
+pub fn tokenize<T: AsRef<[u8]> + ?Sized>(input: &T) -> Vec<SpannedToken> {
+    let bytes = input.as_ref();
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while position < bytes.len() {
+        match read_token_at(bytes, position) {
+            Some((token, end)) => {
+                tokens.push(SpannedToken { token, span: Span { start: position, end } });
+                position = end;
             }
+            None => position += 1
         }
     }
 
-    return patterns;
+    return tokens;
 }
 
-pub fn calculate_result_of_multiplications(input: &str) -> i32 {
-    let patterns = find_all_patterns(input);
-    let mut sum = 0;
-    let mut is_multiplying_active: bool = true;
-
-    for pattern in patterns.into_iter() {
+/// The token starting exactly at `start` and the position right after it.
+fn read_token_at(bytes: &[u8], start: usize) -> Option<(Token, usize)> {
+    let rest = &bytes[start..];
 
-        if (pattern == "do()") {
-            is_multiplying_active = true;
-            continue;
+    if rest.starts_with(b"do()") {
+        return Some((Token::Do, start + 4));
+    }
+    if rest.starts_with(b"don't()") {
+        return Some((Token::Dont, start + 7));
+    }
+    if rest.starts_with(b"mul(") {
+        let (left, after_left) = read_number(bytes, start + 4)?;
+        if bytes.get(after_left) != Some(&b',') {
+            return None;
         }
-        if (is_multiplying_active) {
-            if (pattern == "don't()") {
-                is_multiplying_active = false;
-                continue;
+        let (right, after_right) = read_number(bytes, after_left + 1)?;
+        if bytes.get(after_right) != Some(&b')') {
+            return None;
+        }
+        return Some((Token::Mul(left, right), after_right + 1));
+    }
+
+    return None;
+}
+
+/// One to nine digits, a longer number is not a valid operand.
+pub(crate) fn read_number(bytes: &[u8], start: usize) -> Option<(i32, usize)> {
+    let mut end = start;
+    while end < bytes.len() && bytes[end].is_ascii_digit() {
+        end += 1;
+    }
+    if end == start || end - start > 9 {
+        return None;
+    }
+
+    let mut number = 0;
+    for digit in &bytes[start..end] {
+        number = number * 10 + (digit - b'0') as i32;
+    }
+    return Some((number, end));
+}
+
+/// Runs the instructions one by one. `do()` and `don't()` switch multiplications on and off,
+/// unless `handles_conditionals` is false, in which case every multiplication counts.
+/// The sum is 128 bits, as ten of the largest products are already more than 64 bits hold.
+pub struct Interpreter {
+    pub handles_conditionals: bool,
+    pub is_multiplying_active: bool,
+    pub sum: i128
+}
+
+impl Interpreter {
+
+    pub fn new(handles_conditionals: bool) -> Interpreter {
+        return Interpreter { handles_conditionals, is_multiplying_active: true, sum: 0 };
+    }
+
+    pub fn execute(&mut self, token: &Token) {
+        match token {
+            Token::Do => self.is_multiplying_active = true,
+            Token::Dont => self.is_multiplying_active = !self.handles_conditionals,
+            Token::Mul(left, right) => {
+                if self.is_multiplying_active {
+                    self.sum += mul(*left, *right) as i128;
+                }
             }
         }
-        else {
-            continue;
+    }
+
+    pub fn run(&mut self, tokens: &Vec<SpannedToken>) -> i128 {
+        for spanned_token in tokens {
+            self.execute(&spanned_token.token);
         }
+        return self.sum;
+    }
+}
 
-        //println!("Pattern: {:?}", pattern);
+pub fn find_first_pattern_in_string(input_string: &str) -> Option<&str> {
+    return match tokenize(input_string).first() {
+        None => None,
+        Some(spanned_token) => Some(&input_string[spanned_token.span.start..spanned_token.span.end])
+    };
+}
+
+pub fn find_all_patterns(input: &str) -> Vec<&str> {
+    return tokenize(input).iter()
+        .map(|spanned_token| &input[spanned_token.span.start..spanned_token.span.end])
+        .collect();
+}
 
-        let left_number_as_str = &pattern[pattern.find("(").unwrap()..pattern.find(",").unwrap()];
-        //println!("Left number as string : {}", left_number_as_str);
-        let left_number = pattern[pattern.find("(").unwrap()+1..pattern.find(",").unwrap()].parse().unwrap();
-        //println!("Right number as string : {}", &pattern[pattern.find(",").unwrap()..pattern.find(")").unwrap()]);
-        let right_number = pattern[pattern.find(",").unwrap()+1..pattern.find(")").unwrap()].parse().unwrap();
+/// Part 1, every multiplication counts.
+pub fn calculate_result_of_all_multiplications<T: AsRef<[u8]> + ?Sized>(input: &T) -> i128 {
+    return Interpreter::new(false).run(&tokenize(input));
+}
 
-        sum += mul(left_number, right_number);
-    }
+pub fn calculate_result_of_multiplications<T: AsRef<[u8]> + ?Sized>(input: &T) -> i128 {
+    return Interpreter::new(true).run(&tokenize(input));
+}
 
-    return sum;
+/// Reads the memory in chunks as it scans it, see `streaming::calculate_multiplications_streaming`.
+pub fn calculate_result_of_multiplications_from_reader<R: Read>(reader: R) -> io::Result<i128> {
+    let totals = streaming::calculate_multiplications_streaming(reader, streaming::DEFAULT_CHUNK_SIZE)?;
+    return Ok(totals.enabled);
+}
+
+/// Corrupted memory is a flat stream of bytes, so the whole of it is read before scanning.
+/// It is kept as bytes, not necessarily UTF-8, so the offsets of the tokens are those of the input.
+pub fn read_memory<R: Read>(mut reader: R) -> io::Result<Vec<u8>> {
+    let mut memory = Vec::new();
+    reader.read_to_end(&mut memory)?;
+
+    return Ok(memory);
 }
 
 #[cfg(test)]
@@ -139,4 +239,75 @@ mod tests {
 
         assert_eq!(48, result);
     }
+
+    #[test]
+    fn example_from_reader() {
+        let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+        let result = calculate_result_of_multiplications_from_reader(input.as_bytes()).unwrap();
+
+        assert_eq!(48, result);
+    }
+
+    #[test]
+    fn given_example_tokens_have_spans() {
+        let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+        let result = tokenize(input);
+
+        assert_eq!(result.len(), 6);
+        assert_eq!(result[0], SpannedToken { token: Token::Mul(2, 4), span: Span { start: 1, end: 9 } });
+        assert_eq!(result[1], SpannedToken { token: Token::Dont, span: Span { start: 20, end: 27 } });
+        assert_eq!(result[4].token, Token::Do);
+        assert_eq!(result[5].token, Token::Mul(8, 5));
+    }
+
+    #[test]
+    fn given_too_many_digits_mul_is_skipped() {
+        let input = "mul(1234567890,1)mul(123456789,1)";
+
+        let result = tokenize(input);
+
+        assert_eq!(result, [SpannedToken { token: Token::Mul(123456789, 1), span: Span { start: 17, end: 33 } }]);
+    }
+
+    #[test]
+    fn example_part_one_ignores_conditionals() {
+        let input = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+
+        let result = calculate_result_of_all_multiplications(input);
+
+        assert_eq!(161, result);
+    }
+
+    #[test]
+    fn given_largest_operands_sum_does_not_overflow() {
+        let input = "mul(999999999,999999999)".repeat(10);
+
+        let result = calculate_result_of_all_multiplications(&input);
+
+        assert_eq!(mul(999999999, 999999999), 999999998000000001);
+        assert_eq!(result, 9999999980000000010);
+    }
+
+    #[test]
+    fn given_invalid_utf8_memory_offsets_are_those_of_the_bytes() {
+        let memory = read_memory(&b"\xff\xfe\xfdmul(2,3)\xc3"[..]).unwrap();
+
+        let result = tokenize(&memory);
+
+        assert_eq!(result, [SpannedToken { token: Token::Mul(2, 3), span: Span { start: 3, end: 11 } }]);
+        assert_eq!(calculate_result_of_multiplications(&memory), 6);
+    }
+
+    #[test]
+    fn given_dont_interpreter_state_is_inactive() {
+        let mut interpreter = Interpreter::new(true);
+
+        interpreter.execute(&Token::Dont);
+        interpreter.execute(&Token::Mul(2, 3));
+
+        assert_eq!(interpreter.is_multiplying_active, false);
+        assert_eq!(interpreter.sum, 0);
+    }
 }
@@ -0,0 +1,96 @@
+use std::io::{self, ErrorKind, Read};
+
+use crate::{read_token_at, Interpreter};
+
+/// How many bytes are read from the reader at once.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// `mul(999999999,999999999)`, no instruction is longer. Whether an instruction starts at some position
+/// is decided by the bytes up to this far from it, so only that much has to be kept between chunks.
+const LONGEST_INSTRUCTION: usize = 24;
+
+#[derive(Debug, PartialEq)]
+pub struct MultiplicationTotals {
+    /// Part 1, every multiplication counts.
+    pub all: i128,
+    /// Part 2, only multiplications enabled by `do()` and `don't()` count.
+    pub enabled: i128
+}
+
+/// Same results as `calculate_result_of_all_multiplications` and `calculate_result_of_multiplications`, without
+/// reading the whole memory first. At most `chunk_size` bytes plus the tail of the previous chunk are held at once:
+/// positions too close to the end of a chunk to decide are carried over, so instructions split between chunks are found.
+/// Each token is run by an `Interpreter` per part, as soon as it is found.
+pub fn calculate_multiplications_streaming<R: Read>(mut reader: R, chunk_size: usize) -> io::Result<MultiplicationTotals> {
+    let mut all = Interpreter::new(false);
+    let mut enabled = Interpreter::new(true);
+    let mut chunk = vec![0; chunk_size.max(1)];
+    let mut buffer: Vec<u8> = Vec::with_capacity(chunk.len() + LONGEST_INSTRUCTION);
+
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e)
+        };
+        let is_end = read == 0;
+        buffer.extend_from_slice(&chunk[..read]);
+
+        let decided = match is_end {
+            true => buffer.len(),
+            false => buffer.len().saturating_sub(LONGEST_INSTRUCTION)
+        };
+        let mut position = 0;
+        while position < decided {
+            match read_token_at(&buffer, position) {
+                Some((token, end)) => {
+                    all.execute(&token);
+                    enabled.execute(&token);
+                    position = end;
+                }
+                None => position += 1
+            }
+        }
+
+        if is_end {
+            return Ok(MultiplicationTotals { all: all.sum, enabled: enabled.sum });
+        }
+        buffer.drain(..position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate_result_of_all_multiplications, calculate_result_of_multiplications};
+
+    const EXAMPLE: &str = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn given_example_every_chunk_size_gives_same_totals() {
+        for chunk_size in 1..=EXAMPLE.len() + 1 {
+            let result = calculate_multiplications_streaming(EXAMPLE.as_bytes(), chunk_size).unwrap();
+
+            assert_eq!(result, MultiplicationTotals { all: 161, enabled: 48 }, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn given_large_operands_totals_do_not_overflow() {
+        let input = "mul(999999999,999999999)".repeat(1000);
+
+        let result = calculate_multiplications_streaming(input.as_bytes(), 10).unwrap();
+
+        assert_eq!(result.all, 999999999i128 * 999999999 * 1000);
+    }
+
+    #[test]
+    fn given_long_memory_streaming_matches_in_memory() {
+        let input = EXAMPLE.repeat(50) + "mul(1234,5)don'tmul(1,1)do()mul(1234567890,1)mul(7,7";
+
+        let result = calculate_multiplications_streaming(input.as_bytes(), 7).unwrap();
+
+        assert_eq!(result.all, calculate_result_of_all_multiplications(&input));
+        assert_eq!(result.enabled, calculate_result_of_multiplications(&input));
+    }
+}
@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use day4::find_words;
+use day4::multi_search::find_words_fast;
+
+const LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// A square grid of random capital letters, with a fixed seed so runs compare.
+fn generate_grid(rng: &mut StdRng, size: usize) -> Vec<Vec<char>> {
+    return (0..size)
+        .map(|_| (0..size).map(|_| LETTERS[rng.gen_range(0..LETTERS.len())] as char).collect())
+        .collect();
+}
+
+fn generate_words(rng: &mut StdRng, count: usize) -> Vec<String> {
+    return (0..count)
+        .map(|_| (0..rng.gen_range(3..=8)).map(|_| LETTERS[rng.gen_range(0..LETTERS.len())] as char).collect())
+        .collect();
+}
+
+fn bench_word_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("word_search");
+    group.sample_size(10);
+    let mut rng = StdRng::seed_from_u64(2024);
+    let words = generate_words(&mut rng, 300);
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+    // Trying every word at every cell in every direction, 2000x2000 would take minutes.
+    let grid = generate_grid(&mut rng, 200);
+    group.bench_with_input(BenchmarkId::new("each_cell", 200), &grid, |b, grid| {
+        b.iter(|| find_words(black_box(grid), &words))
+    });
+
+    for size in [200, 2000] {
+        let grid = generate_grid(&mut rng, size);
+        group.bench_with_input(BenchmarkId::new("automaton", size), &grid, |b, grid| {
+            b.iter(|| find_words_fast(black_box(grid), &words))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_word_search);
+criterion_main!(benches);
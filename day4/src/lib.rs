@@ -1,38 +1,117 @@
 use strum::IntoEnumIterator; // 0.17.1
 use strum_macros::EnumIter; // 0.17.1
+use std::collections::HashMap;
 
+use multi_search::{find_words_fast, find_words_fast_wrapping};
+use template::{find_template, Symmetry, Template};
+
+pub mod multi_search;
+pub mod render;
+pub mod template;
+
+/// Part 2: two `MAS` crossing diagonally on their `A`, each of them forwards or backwards.
 pub fn find_patterns_xmas(input: &str) -> i32 {
     let lines = read_all_lines(input);
-    let mut total_patterns = 0;
-    let mut all_as_in_mases: Vec<APosition> = Vec::new();
-
-    for i in 0..lines.len() {
-        let current_sub_vec: Vec<char> = lines.get(i).unwrap().clone();
-
-        for j in 0..current_sub_vec.len() {
-            if current_sub_vec[j] == 'M' {
-                for direction in Direction::iter() {
-                    let element = get_element_in_direction_with_step(direction, j, i, lines.clone(), 1);
-                    if element == Some('A') {
-                        let element2 = get_element_in_direction_with_step(direction, j, i, lines.clone(), 2);
-                        if element2 == Some('S') {
-                            let aposition = get_position_of_a(direction, j, i);
-                            if all_as_in_mases.contains(&aposition) {
-                                total_patterns += 1;
-                            }
-                            else {
-                                all_as_in_mases.push(aposition);
-                            }
-                        }
+    let mut crossings: HashMap<APosition, i32> = HashMap::new();
+
+    for word_match in find_words_fast(&lines, &["MAS"]) {
+        if word_match.direction.is_diagonal() {
+            let a_position = get_position_of_a(word_match.direction, word_match.x, word_match.y);
+            *crossings.entry(a_position).or_insert(0) += 1;
+        }
+    }
+
+    return crossings.values().filter(|count| **count == 2).count() as i32;
+}
+
+/// The X-MAS of part 2 as a template, any rotation of it counts.
+pub const X_MAS_TEMPLATE: &str = "M.S/.A./M.S";
+
+/// Part 2 again, declared as a template instead of crossing words.
+pub fn count_x_mas_with_template(input: &str) -> usize {
+    let template = Template::parse(X_MAS_TEMPLATE).unwrap();
+    return find_template(&read_all_lines(input), &template, Symmetry::Rotations).len();
+}
+
+/// Part 1: every `XMAS`, in any of the eight directions.
+pub fn count_xmas(input: &str) -> usize {
+    return find_words_fast(&read_all_lines(input), &["XMAS"]).len();
+}
+
+/// Part 1 with the edges of the grid joined, see `find_words_wrapping`.
+pub fn count_xmas_wrapping(input: &str) -> usize {
+    return find_words_fast_wrapping(&read_all_lines(input), &["XMAS"]).len();
+}
+
+/// A word found in the grid, starting at column `x` of line `y` and read towards `direction`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WordMatch<'a> {
+    pub word: &'a str,
+    pub x: usize,
+    pub y: usize,
+    pub direction: Direction
+}
+
+/// Every occurrence of every word, in all eight directions. A word that reads the same backwards,
+/// or a single letter, is found once per direction it can be read in.
+/// Each word is tried at each cell, see `find_words_fast` for many words or large grids.
+pub fn find_words<'a>(lines: &[Vec<char>], words: &[&'a str]) -> Vec<WordMatch<'a>> {
+    return search_words(lines, words, false);
+}
+
+/// Like `find_words`, with the edges of the grid joined: reading past the end of a line continues at its start,
+/// and past the last line on the first one. A word can then be longer than the grid and go round it more than once.
+/// See `find_words_fast_wrapping` for many words or large grids.
+pub fn find_words_wrapping<'a>(lines: &[Vec<char>], words: &[&'a str]) -> Vec<WordMatch<'a>> {
+    return search_words(lines, words, true);
+}
+
+fn search_words<'a>(lines: &[Vec<char>], words: &[&'a str], wraps: bool) -> Vec<WordMatch<'a>> {
+    let letters: Vec<Vec<char>> = words.iter().map(|word| read_line(word)).collect();
+    let mut matches = Vec::new();
+
+    for y in 0..lines.len() {
+        for x in 0..lines[y].len() {
+            for direction in Direction::iter() {
+                for (word, word_letters) in words.iter().zip(&letters) {
+                    if is_word_at(lines, word_letters, x, y, direction, wraps) {
+                        matches.push(WordMatch { word, x, y, direction });
                     }
                 }
             }
         }
     }
 
+    return matches;
+}
 
+fn is_word_at(lines: &[Vec<char>], word: &[char], x: usize, y: usize, direction: Direction, wraps: bool) -> bool {
+    if word.is_empty() {
+        return false;
+    }
+    let (horizontal_diff, vertical_diff) = direction.offset();
+
+    for (i, expected_letter) in word.iter().enumerate() {
+        let mut target_x = x as i64 + horizontal_diff as i64 * i as i64;
+        let mut target_y = y as i64 + vertical_diff as i64 * i as i64;
+        if wraps {
+            target_y = target_y.rem_euclid(lines.len() as i64);
+            let line_length = lines[target_y as usize].len() as i64;
+            if line_length == 0 {
+                return false;
+            }
+            target_x = target_x.rem_euclid(line_length);
+        }
+        if target_y < 0 || target_y >= lines.len() as i64 || target_x < 0 {
+            return false;
+        }
+        match lines[target_y as usize].get(target_x as usize) {
+            Some(letter) if letter == expected_letter => {}
+            _ => return false
+        }
+    }
 
-    return total_patterns;
+    return true;
 }
 
 pub fn read_line(line: &str) -> Vec<char> {
@@ -47,37 +126,16 @@ pub fn read_all_lines(lines: &str) -> Vec<Vec<char>> {
     return lines_vec;
 }
 
-pub fn get_element_in_direction(dir: Direction, pivot_x: usize, pivot_y: usize, lines: Vec<Vec<char>>) -> Option<char> {
+pub fn get_element_in_direction(dir: Direction, pivot_x: usize, pivot_y: usize, lines: &[Vec<char>]) -> Option<char> {
     return get_element_in_direction_with_step(dir, pivot_x, pivot_y, lines, 1)
 }
 
-pub fn get_element_in_direction_with_step(dir: Direction, pivot_x: usize, pivot_y: usize, lines: Vec<Vec<char>>, step: i32) -> Option<char> {
+pub fn get_element_in_direction_with_step(dir: Direction, pivot_x: usize, pivot_y: usize, lines: &[Vec<char>], step: i32) -> Option<char> {
     let pivot_x_as_i32: i32 = pivot_x as i32;
     let pivot_y_as_i32: i32 = pivot_y as i32;
-    let mut vertical_diff = 0;
-    let mut horizontal_diff = 0;
-    match dir {
-        //Direction::North => vertical_diff = -step,
-        Direction::NorthEast => {
-            vertical_diff = -step;
-            horizontal_diff = step;
-        },
-        //Direction::East => horizontal_diff = step,
-        Direction::SouthEast => {
-            vertical_diff = step;
-            horizontal_diff = step;
-        },
-        //Direction::South => vertical_diff = step,
-        Direction::SouthWest =>  {
-            vertical_diff = step;
-            horizontal_diff = -step;
-        },
-        //Direction::West => horizontal_diff = -step,
-        Direction::NorthWest =>  {
-            vertical_diff = -step;
-            horizontal_diff = -step;
-        },
-    }
+    let (horizontal_diff, vertical_diff) = dir.offset();
+    let horizontal_diff = horizontal_diff * step;
+    let vertical_diff = vertical_diff * step;
 
     if pivot_y_as_i32 + vertical_diff < 0 || pivot_y_as_i32 + vertical_diff >= lines.len() as i32 {
         return None
@@ -94,63 +152,54 @@ pub fn get_element_in_direction_with_step(dir: Direction, pivot_x: usize, pivot_
 }
 
 pub fn get_position_of_a(dir: Direction, pivot_x: usize, pivot_y: usize) -> APosition {
-    let pivot_x_as_i32: i32 = pivot_x as i32;
-    let pivot_y_as_i32: i32 = pivot_y as i32;
-    let step = 1;
-    let mut vertical_diff = 0;
-    let mut horizontal_diff = 0;
-    match dir {
-        //Direction::North => vertical_diff = -step,
-        Direction::NorthEast => {
-            vertical_diff = -step;
-            horizontal_diff = step;
-        },
-        //Direction::East => horizontal_diff = step,
-        Direction::SouthEast => {
-            vertical_diff = step;
-            horizontal_diff = step;
-        },
-        //Direction::South => vertical_diff = step,
-        Direction::SouthWest =>  {
-            vertical_diff = step;
-            horizontal_diff = -step;
-        },
-        //Direction::West => horizontal_diff = -step,
-        Direction::NorthWest =>  {
-            vertical_diff = -step;
-            horizontal_diff = -step;
-        },
-    }
+    let (horizontal_diff, vertical_diff) = dir.offset();
 
-    let target_y = (pivot_y_as_i32 + vertical_diff) as usize;
-    let target_x = (pivot_x_as_i32 + horizontal_diff) as usize;
+    let target_y = (pivot_y as i32 + vertical_diff) as usize;
+    let target_x = (pivot_x as i32 + horizontal_diff) as usize;
 
     return APosition {x: target_x, y: target_y};
 }
 
-struct APosition {
-    x: usize,
-    y: usize
-}
-
-impl PartialEq<APosition> for APosition {
-    fn eq(&self, other: &APosition) -> bool {
-        return other.x == self.x && other.y == self.y;
-    }
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct APosition {
+    pub x: usize,
+    pub y: usize
 }
 
-#[derive(Copy, Clone, Debug, EnumIter)]
-enum Direction {
-    //North,
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq)]
+pub enum Direction {
+    North,
     NorthEast,
-    //East,
+    East,
     SouthEast,
-    //South,
+    South,
     SouthWest,
-    //West,
+    West,
     NorthWest
 }
 
+impl Direction {
+
+    /// Horizontal and vertical difference of one step, lines go down.
+    pub fn offset(&self) -> (i32, i32) {
+        return match self {
+            Direction::North => (0, -1),
+            Direction::NorthEast => (1, -1),
+            Direction::East => (1, 0),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (0, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (-1, -1)
+        };
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        let (horizontal_diff, vertical_diff) = self.offset();
+        return horizontal_diff != 0 && vertical_diff != 0;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,13 +224,13 @@ mod tests {
         assert_eq!(result, [['1', '2', '3'], ['4', '5', '6'], ['7', '8', '9']]);
     }
 
-    /*#[test]
+    #[test]
     fn given_many_lines_when_reading_lines_two_is_north_of_five() {
         let lines_as_vecs = Vec::from([Vec::from(['1', '2', '3']), Vec::from(['4', '5', '6']), Vec::from(['7', '8', '9'])]);
         let pivot_x = 1;
         let pivot_y = 1;
 
-        let result = get_element_in_direction(Direction::North, pivot_x, pivot_y, lines_as_vecs);
+        let result = get_element_in_direction(Direction::North, pivot_x, pivot_y, &lines_as_vecs);
 
         assert_eq!(result, Some('2'));
     }
@@ -192,7 +241,7 @@ mod tests {
         let pivot_x = 1;
         let pivot_y = 1;
 
-        let result = get_element_in_direction(Direction::West, pivot_x, pivot_y, lines_as_vecs);
+        let result = get_element_in_direction(Direction::West, pivot_x, pivot_y, &lines_as_vecs);
 
         assert_eq!(result, Some('4'));
     }
@@ -203,8 +252,10 @@ mod tests {
         let pivot_x = 0;
         let pivot_y = 1;
 
-        let result = get_element_in_direction(Direction::West, pivot_x, pivot_y, lines_as_vecs);
-    }*/
+        let result = get_element_in_direction(Direction::West, pivot_x, pivot_y, &lines_as_vecs);
+
+        assert_eq!(result, None);
+    }
 
     #[test]
     fn example() {
@@ -223,4 +274,81 @@ MXMXAXMASX";
 
         assert_eq!(result, 9);
     }
+
+    #[test]
+    fn example_part_one() {
+        let input = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+        let result = count_xmas(input);
+
+        assert_eq!(result, 18);
+    }
+
+    #[test]
+    fn given_words_find_words_returns_start_and_direction() {
+        let lines = read_all_lines("CAT
+XAX
+TOC");
+
+        let result = find_words(&lines, &["CAT", "TOC", "AA", "CAC"]);
+
+        assert_eq!(result, [
+            WordMatch { word: "CAT", x: 0, y: 0, direction: Direction::East },
+            WordMatch { word: "CAC", x: 0, y: 0, direction: Direction::SouthEast },
+            WordMatch { word: "AA", x: 1, y: 0, direction: Direction::South },
+            WordMatch { word: "AA", x: 1, y: 1, direction: Direction::North },
+            WordMatch { word: "TOC", x: 0, y: 2, direction: Direction::East },
+            WordMatch { word: "CAC", x: 2, y: 2, direction: Direction::NorthWest }
+        ]);
+    }
+
+    #[test]
+    fn given_example_wrapping_finds_more_xmas() {
+        let input = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+        let result = count_xmas_wrapping(input);
+
+        assert_eq!(result, find_words_wrapping(&read_all_lines(input), &["XMAS"]).len());
+        assert!(result > count_xmas(input));
+    }
+
+    #[test]
+    fn given_wrapping_search_words_continue_on_the_other_side() {
+        let lines = read_all_lines("SXMA
+ABCD");
+
+        assert_eq!(find_words(&lines, &["XMAS"]), []);
+        assert_eq!(find_words_wrapping(&lines, &["XMAS"]), [WordMatch { word: "XMAS", x: 1, y: 0, direction: Direction::East }]);
+    }
+
+    #[test]
+    fn given_wrapping_search_words_longer_than_grid_are_found() {
+        let lines = read_all_lines("AB
+CD");
+
+        let result = find_words_wrapping(&lines, &["ABABABA"]);
+
+        assert_eq!(result, [
+            WordMatch { word: "ABABABA", x: 0, y: 0, direction: Direction::East },
+            WordMatch { word: "ABABABA", x: 0, y: 0, direction: Direction::West }
+        ]);
+    }
 }
@@ -0,0 +1,303 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::{find_words_wrapping, Direction, WordMatch};
+
+/// An Aho-Corasick automaton over the letters of the words it was built from. Each word is added forwards and
+/// backwards, so one pass over a line finds the words read in both of its directions.
+pub struct WordAutomaton<'a> {
+    words: Vec<&'a str>,
+    alphabet: HashMap<char, usize>,
+    /// `transitions[node][letter]`, already following failure links, so every letter is one step.
+    transitions: Vec<Vec<usize>>,
+    /// The patterns ending at each node, including those of its failure links: the word index,
+    /// its length, and whether the pattern is the word backwards.
+    outputs: Vec<Vec<(usize, usize, bool)>>
+}
+
+impl<'a> WordAutomaton<'a> {
+
+    pub fn new(words: &[&'a str]) -> WordAutomaton<'a> {
+        let mut alphabet = HashMap::new();
+        for word in words {
+            for letter in word.chars() {
+                let next_id = alphabet.len();
+                alphabet.entry(letter).or_insert(next_id);
+            }
+        }
+
+        let mut trie: Vec<Vec<Option<usize>>> = vec![vec![None; alphabet.len()]];
+        let mut outputs: Vec<Vec<(usize, usize, bool)>> = vec![Vec::new()];
+        for (index, word) in words.iter().enumerate() {
+            let letters: Vec<usize> = word.chars().map(|letter| alphabet[&letter]).collect();
+            if letters.is_empty() {
+                continue;
+            }
+            let backwards: Vec<usize> = letters.iter().rev().copied().collect();
+
+            for (pattern, is_backwards) in [(letters.clone(), false), (backwards, true)] {
+                let mut node = 0;
+                for letter in pattern {
+                    node = match trie[node][letter] {
+                        Some(next) => next,
+                        None => {
+                            trie.push(vec![None; alphabet.len()]);
+                            outputs.push(Vec::new());
+                            trie[node][letter] = Some(trie.len() - 1);
+                            trie.len() - 1
+                        }
+                    };
+                }
+                outputs[node].push((index, letters.len(), is_backwards));
+            }
+        }
+
+        // Breadth first, so the failure link of a node is complete before its children need it.
+        let mut transitions = vec![vec![0; alphabet.len()]; trie.len()];
+        let mut failures = vec![0; trie.len()];
+        let mut queue = VecDeque::new();
+        for letter in 0..alphabet.len() {
+            if let Some(child) = trie[0][letter] {
+                transitions[0][letter] = child;
+                queue.push_back(child);
+            }
+        }
+        while let Some(node) = queue.pop_front() {
+            let inherited = outputs[failures[node]].clone();
+            outputs[node].extend(inherited);
+
+            for letter in 0..alphabet.len() {
+                match trie[node][letter] {
+                    Some(child) => {
+                        failures[child] = transitions[failures[node]][letter];
+                        transitions[node][letter] = child;
+                        queue.push_back(child);
+                    }
+                    None => transitions[node][letter] = transitions[failures[node]][letter]
+                }
+            }
+        }
+
+        return WordAutomaton { words: words.to_vec(), alphabet, transitions, outputs };
+    }
+
+    /// Feeds one line of the grid to the automaton, `cell_at(i)` being its `i`th cell until it returns `None`.
+    /// Each match is reported from the cell its word starts on, in the direction it reads. On a line that is a cycle,
+    /// read more than once round, only the matches starting in the first round are kept.
+    fn search_line(&self, lines: &[Vec<char>], line: LineReading, cell_at: impl Fn(usize) -> Option<(usize, usize)>,
+                   matches: &mut Vec<WordMatch<'a>>) {
+        let mut node = 0;
+        let mut i = 0;
+        while let Some((x, y)) = cell_at(i) {
+            node = match self.alphabet.get(&lines[y][x]) {
+                Some(letter) => self.transitions[node][*letter],
+                None => 0
+            };
+
+            for (index, length, is_backwards) in &self.outputs[node] {
+                let start = i + 1 - length;
+                if line.cycle_length.is_some_and(|cycle_length| start >= cycle_length) {
+                    continue;
+                }
+                let word = self.words[*index];
+                let word_match = match is_backwards {
+                    false => {
+                        let (start_x, start_y) = cell_at(start).unwrap();
+                        WordMatch { word, x: start_x, y: start_y, direction: line.direction }
+                    }
+                    true => WordMatch { word, x, y, direction: line.opposite }
+                };
+                matches.push(word_match);
+            }
+            i += 1;
+        }
+    }
+}
+
+/// How a line of the grid is read: towards `direction`, with the words read backwards going towards `opposite`.
+/// A line that is a cycle comes back to its first cell after `cycle_length` cells.
+#[derive(Clone, Copy)]
+struct LineReading {
+    direction: Direction,
+    opposite: Direction,
+    cycle_length: Option<usize>
+}
+
+/// Each direction with its opposite, whose matches are found on the same lines read backwards.
+const DIRECTION_PAIRS: [(Direction, Direction); 4] = [
+    (Direction::East, Direction::West),
+    (Direction::South, Direction::North),
+    (Direction::SouthEast, Direction::NorthWest),
+    (Direction::SouthWest, Direction::NorthEast)
+];
+
+/// Same matches as `find_words`, in another order. Every row, column and diagonal is read once, whatever the
+/// number of words, so the time grows with the size of the grid and the number of matches rather than with the words.
+pub fn find_words_fast<'a>(lines: &[Vec<char>], words: &[&'a str]) -> Vec<WordMatch<'a>> {
+    let automaton = WordAutomaton::new(words);
+    let mut matches = Vec::new();
+
+    for (direction, opposite) in DIRECTION_PAIRS {
+        let (horizontal_diff, vertical_diff) = direction.offset();
+        for (start_x, start_y) in line_starts(lines, direction) {
+            let cell_at = |i: usize| {
+                let (x, y) = (start_x as i64 + horizontal_diff as i64 * i as i64, start_y as i64 + vertical_diff as i64 * i as i64);
+                is_inside(lines, x, y).then_some((x as usize, y as usize))
+            };
+            automaton.search_line(lines, LineReading { direction, opposite, cycle_length: None }, cell_at, &mut matches);
+        }
+    }
+
+    return matches;
+}
+
+/// Same matches as `find_words_wrapping`, in another order. On a rectangular grid whose edges are joined, every line
+/// is a cycle, which is read round once plus the length of the longest word, so words going round are found too.
+/// Rows of different lengths do not join into cycles, such a grid is searched with `find_words_wrapping` instead.
+pub fn find_words_fast_wrapping<'a>(lines: &[Vec<char>], words: &[&'a str]) -> Vec<WordMatch<'a>> {
+    let width = lines.first().map_or(0, |line| line.len());
+    if width == 0 || lines.iter().any(|line| line.len() != width) {
+        return find_words_wrapping(lines, words);
+    }
+    let height = lines.len();
+    let automaton = WordAutomaton::new(words);
+    let longest_word = words.iter().map(|word| word.chars().count()).max().unwrap_or(0);
+    let mut matches = Vec::new();
+
+    for (direction, opposite) in DIRECTION_PAIRS {
+        let (horizontal_diff, vertical_diff) = direction.offset();
+        let horizontal_period = if horizontal_diff == 0 { 1 } else { width };
+        let vertical_period = if vertical_diff == 0 { 1 } else { height };
+        let cycle_length = horizontal_period / greatest_common_divisor(horizontal_period, vertical_period) * vertical_period;
+
+        let mut is_visited = vec![vec![false; width]; height];
+        for y in 0..height {
+            for x in 0..width {
+                if is_visited[y][x] {
+                    continue;
+                }
+                let cell_at = |i: usize| (i < cycle_length + longest_word.saturating_sub(1)).then(|| (
+                    (x as i64 + horizontal_diff as i64 * i as i64).rem_euclid(width as i64) as usize,
+                    (y as i64 + vertical_diff as i64 * i as i64).rem_euclid(height as i64) as usize
+                ));
+                for i in 0..cycle_length {
+                    let (cycle_x, cycle_y) = cell_at(i).unwrap();
+                    is_visited[cycle_y][cycle_x] = true;
+                }
+                automaton.search_line(lines, LineReading { direction, opposite, cycle_length: Some(cycle_length) }, cell_at, &mut matches);
+            }
+        }
+    }
+
+    return matches;
+}
+
+fn greatest_common_divisor(a: usize, b: usize) -> usize {
+    return if b == 0 { a } else { greatest_common_divisor(b, a % b) };
+}
+
+/// The first cell of each line read towards `direction`: the cells whose previous one would be outside the grid.
+/// A line also stops at the end of a row shorter than the others. The cells are produced as they are needed,
+/// so no line is ever held in memory.
+fn line_starts(lines: &[Vec<char>], direction: Direction) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let (horizontal_diff, vertical_diff) = direction.offset();
+
+    return (0..lines.len())
+        .flat_map(move |y| (0..lines[y].len()).map(move |x| (x, y)))
+        .filter(move |(x, y)| !is_inside(lines, *x as i64 - horizontal_diff as i64, *y as i64 - vertical_diff as i64));
+}
+
+fn is_inside(lines: &[Vec<char>], x: i64, y: i64) -> bool {
+    return y >= 0 && (y as usize) < lines.len() && x >= 0 && (x as usize) < lines[y as usize].len();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{find_words, find_words_wrapping, read_all_lines};
+
+    fn sorted(mut matches: Vec<WordMatch>) -> Vec<WordMatch> {
+        matches.sort_by_key(|word_match| (word_match.y, word_match.x, word_match.direction as usize, word_match.word));
+        return matches;
+    }
+
+    #[test]
+    fn given_example_fast_search_finds_same_words() {
+        let lines = read_all_lines("MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX");
+        let words = ["XMAS", "MAS", "AM", "SAMX", "M", "MXM"];
+
+        let result = find_words_fast(&lines, &words);
+
+        assert_eq!(result.iter().filter(|word_match| word_match.word == "XMAS").count(), 18);
+        assert_eq!(sorted(result), sorted(find_words(&lines, &words)));
+    }
+
+    #[test]
+    fn given_ragged_grid_fast_search_finds_same_words() {
+        let lines = read_all_lines("ABCD
+BC
+CDAB
+D");
+        let words = ["BC", "CB", "ABC", "DC", "CD"];
+
+        let result = find_words_fast(&lines, &words);
+
+        assert_eq!(sorted(result), sorted(find_words(&lines, &words)));
+    }
+
+    #[test]
+    fn given_words_sharing_suffixes_every_one_is_found() {
+        let lines = read_all_lines("SHEHERSHIS");
+
+        let result = find_words_fast(&lines, &["HE", "SHE", "HERS", "HIS"]);
+
+        let found: Vec<(&str, usize)> = sorted(result).iter()
+            .filter(|word_match| word_match.direction == Direction::East)
+            .map(|word_match| (word_match.word, word_match.x))
+            .collect();
+        assert_eq!(found, [("SHE", 0), ("HE", 1), ("HE", 3), ("HERS", 3), ("HIS", 7)]);
+    }
+
+    #[test]
+    fn given_wrapping_fast_search_finds_same_words() {
+        let lines = read_all_lines("SXMAS
+AMXSA
+MSAMX
+XMASM");
+        let words = ["XMAS", "SAMX", "AMAX", "M", "MSAMXMSAMX", "SS"];
+
+        let result = find_words_fast_wrapping(&lines, &words);
+
+        assert_eq!(sorted(result), sorted(find_words_wrapping(&lines, &words)));
+    }
+
+    #[test]
+    fn given_word_longer_than_the_grid_wrapping_fast_search_finds_it_once_per_direction() {
+        let lines = read_all_lines("AB
+BA");
+
+        let result = find_words_fast_wrapping(&lines, &["ABABABA"]);
+
+        assert_eq!(sorted(result.clone()), sorted(find_words_wrapping(&lines, &["ABABABA"])));
+        assert_eq!(result.len(), 8);
+    }
+
+    #[test]
+    fn given_ragged_grid_wrapping_fast_search_falls_back() {
+        let lines = read_all_lines("XMASX
+MA
+SAMXM");
+
+        let result = find_words_fast_wrapping(&lines, &["XMAS", "AM"]);
+
+        assert_eq!(sorted(result), sorted(find_words_wrapping(&lines, &["XMAS", "AM"])));
+    }
+}
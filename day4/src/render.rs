@@ -0,0 +1,128 @@
+use crate::WordMatch;
+
+/// Foreground colours cycled through for the matches, normal then bright.
+const PALETTE: [&str; 12] = [
+    "\x1b[31m", "\x1b[32m", "\x1b[33m", "\x1b[34m", "\x1b[35m", "\x1b[36m",
+    "\x1b[91m", "\x1b[92m", "\x1b[93m", "\x1b[94m", "\x1b[95m", "\x1b[96m"
+];
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+
+/// The cells covered by a match, from its first letter to its last, as if the grid went on forever.
+pub fn cells_of(word_match: &WordMatch) -> Vec<(i64, i64)> {
+    let (horizontal_diff, vertical_diff) = word_match.direction.offset();
+    return (0..word_match.word.chars().count() as i64)
+        .map(|i| (word_match.x as i64 + horizontal_diff as i64 * i, word_match.y as i64 + vertical_diff as i64 * i))
+        .collect();
+}
+
+/// For each cell, the index of the first match covering it.
+fn find_covering_matches(lines: &[Vec<char>], matches: &[WordMatch]) -> Vec<Vec<Option<usize>>> {
+    let mut covering: Vec<Vec<Option<usize>>> = lines.iter().map(|line| vec![None; line.len()]).collect();
+
+    for (match_index, word_match) in matches.iter().enumerate() {
+        for (x, y) in cells_of(word_match) {
+            // Matches of `find_words_wrapping` go past the edges and come back on the other side.
+            let y = y.rem_euclid(lines.len() as i64) as usize;
+            let x = x.rem_euclid(lines[y].len() as i64) as usize;
+            if covering[y][x].is_none() {
+                covering[y][x] = Some(match_index);
+            }
+        }
+    }
+
+    return covering;
+}
+
+/// The grid with only the letters of the found words, every other cell is a `.`.
+pub fn render_found_words(lines: &[Vec<char>], matches: &[WordMatch]) -> String {
+    let covering = find_covering_matches(lines, matches);
+
+    let rows: Vec<String> = lines.iter().zip(covering)
+        .map(|(line, covering_row)| line.iter().zip(covering_row)
+            .map(|(letter, match_index)| if match_index.is_some() { *letter } else { '.' })
+            .collect())
+        .collect();
+    return rows.join("\n");
+}
+
+/// Like `render_found_words`, with each match in its own colour, so matches of the same word can be told apart,
+/// and the dots dimmed. A cell shared by several matches takes the colour of the first one covering it.
+pub fn render_found_words_ansi(lines: &[Vec<char>], matches: &[WordMatch]) -> String {
+    let covering = find_covering_matches(lines, matches);
+
+    let mut rows = Vec::new();
+    for (line, covering_row) in lines.iter().zip(covering) {
+        let mut row = String::new();
+        for (letter, match_index) in line.iter().zip(covering_row) {
+            match match_index {
+                Some(index) => row += &format!("{}{}{}", PALETTE[index % PALETTE.len()], letter, RESET),
+                None => row += &format!("{}.{}", DIM, RESET)
+            }
+        }
+        rows.push(row);
+    }
+    return rows.join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{find_words, find_words_wrapping, read_all_lines};
+
+    #[test]
+    fn given_example_render_keeps_only_xmas_letters() {
+        let lines = read_all_lines("MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX");
+
+        let result = render_found_words(&lines, &find_words(&lines, &["XMAS"]));
+
+        assert_eq!(result, "....XXMAS.
+.SAMXMS...
+...S..A...
+..A.A.MS.X
+XMASAMX.MM
+X.....XA.A
+S.S.S.S.SS
+.A.A.A.A.A
+..M.M.M.MM
+.X.X.XMASX");
+    }
+
+    #[test]
+    fn given_same_word_twice_ansi_render_colours_each_match() {
+        let lines = read_all_lines("CATCAT");
+
+        let result = render_found_words_ansi(&lines, &find_words(&lines, &["CAT"]));
+
+        assert_eq!(result, "\x1b[31mC\x1b[0m\x1b[31mA\x1b[0m\x1b[31mT\x1b[0m\x1b[32mC\x1b[0m\x1b[32mA\x1b[0m\x1b[32mT\x1b[0m");
+    }
+
+    #[test]
+    fn given_no_match_ansi_render_dims_every_cell() {
+        let lines = read_all_lines("AB");
+
+        let result = render_found_words_ansi(&lines, &[]);
+
+        assert_eq!(result, "\x1b[2m.\x1b[0m\x1b[2m.\x1b[0m");
+    }
+
+    #[test]
+    fn given_wrapping_matches_render_marks_cells_on_both_sides() {
+        let lines = read_all_lines("SXMA
+ABCD");
+
+        let result = render_found_words(&lines, &find_words_wrapping(&lines, &["XMAS"]));
+
+        assert_eq!(result, "SXMA
+....");
+    }
+}
@@ -0,0 +1,210 @@
+use std::fmt;
+
+/// A rectangle of letters to look for in the grid, where `.` matches any letter.
+/// Rows are separated by `/` or by line breaks, so `M.S/.A./M.S` is the X-MAS of part 2.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    cells: Vec<Vec<Option<char>>>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symmetry {
+    /// Only the template as written.
+    None,
+    /// The template turned by a quarter, half and three quarters too.
+    Rotations,
+    /// Every rotation, and every rotation mirrored.
+    RotationsAndReflections
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TemplateError {
+    Empty,
+    /// Row `row`, counting from 1, is not as long as the first one.
+    RaggedRow { row: usize }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            TemplateError::Empty => write!(f, "Template is empty"),
+            TemplateError::RaggedRow { row } => write!(f, "Template row {} is not as long as the first row", row)
+        };
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Where the top left corner of a template was found, and which of its variants matched.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TemplateMatch {
+    pub x: usize,
+    pub y: usize,
+    pub variant: Template
+}
+
+impl Template {
+
+    pub fn parse(text: &str) -> Result<Template, TemplateError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(TemplateError::Empty);
+        }
+
+        let cells: Vec<Vec<Option<char>>> = text.split(['/', '\n'])
+            .map(|row| row.trim_end_matches('\r').chars().map(|letter| if letter == '.' { None } else { Some(letter) }).collect())
+            .collect();
+        for i in 0..cells.len() {
+            if cells[i].len() != cells[0].len() || cells[i].is_empty() {
+                return Err(TemplateError::RaggedRow { row: i + 1 });
+            }
+        }
+
+        return Ok(Template { cells });
+    }
+
+    pub fn height(&self) -> usize {
+        return self.cells.len();
+    }
+
+    pub fn width(&self) -> usize {
+        return self.cells[0].len();
+    }
+
+    /// Turned a quarter clockwise.
+    pub fn rotated(&self) -> Template {
+        let mut cells = Vec::new();
+        for column in 0..self.width() {
+            let mut row = Vec::new();
+            for line in (0..self.height()).rev() {
+                row.push(self.cells[line][column]);
+            }
+            cells.push(row);
+        }
+        return Template { cells };
+    }
+
+    /// Mirrored left to right.
+    pub fn reflected(&self) -> Template {
+        let cells = self.cells.iter().map(|row| row.iter().rev().copied().collect()).collect();
+        return Template { cells };
+    }
+
+    /// The distinct templates the symmetry allows, this one first. A template that looks the same
+    /// once turned or mirrored is only listed once, so no location is reported twice for it.
+    pub fn variants(&self, symmetry: Symmetry) -> Vec<Template> {
+        let mut candidates = vec![self.clone()];
+        if symmetry != Symmetry::None {
+            for i in 0..3 {
+                let next = candidates[i].rotated();
+                candidates.push(next);
+            }
+        }
+        if symmetry == Symmetry::RotationsAndReflections {
+            for i in 0..4 {
+                let next = candidates[i].reflected();
+                candidates.push(next);
+            }
+        }
+
+        let mut variants: Vec<Template> = Vec::new();
+        for candidate in candidates {
+            if !variants.contains(&candidate) {
+                variants.push(candidate);
+            }
+        }
+        return variants;
+    }
+
+    fn matches_at(&self, lines: &[Vec<char>], x: usize, y: usize) -> bool {
+        for i in 0..self.height() {
+            for j in 0..self.width() {
+                let letter = match lines.get(y + i).and_then(|line| line.get(x + j)) {
+                    Some(letter) => *letter,
+                    None => return false
+                };
+                if self.cells[i][j].is_some_and(|expected| expected != letter) {
+                    return false;
+                }
+            }
+        }
+        return true;
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows: Vec<String> = self.cells.iter()
+            .map(|row| row.iter().map(|cell| cell.unwrap_or('.')).collect())
+            .collect();
+        return write!(f, "{}", rows.join("/"));
+    }
+}
+
+/// Every place where a variant of the template fits entirely inside the grid, row by row.
+pub fn find_template(lines: &[Vec<char>], template: &Template, symmetry: Symmetry) -> Vec<TemplateMatch> {
+    let variants = template.variants(symmetry);
+    let mut matches = Vec::new();
+
+    for y in 0..lines.len() {
+        for x in 0..lines[y].len() {
+            for variant in &variants {
+                if variant.matches_at(lines, x, y) {
+                    matches.push(TemplateMatch { x, y, variant: variant.clone() });
+                }
+            }
+        }
+    }
+
+    return matches;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_all_lines;
+
+    const EXAMPLE: &str = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+    #[test]
+    fn given_x_mas_template_with_rotations_example_has_nine_matches() {
+        let template = Template::parse("M.S/.A./M.S").unwrap();
+
+        let result = find_template(&read_all_lines(EXAMPLE), &template, Symmetry::Rotations);
+
+        assert_eq!(result.len(), 9);
+        assert_eq!(result[0], TemplateMatch { x: 1, y: 0, variant: Template::parse("M.S/.A./M.S").unwrap() });
+    }
+
+    #[test]
+    fn given_template_rotations_turn_clockwise() {
+        let template = Template::parse("AB/CD/EF").unwrap();
+
+        assert_eq!(template.rotated().to_string(), "ECA/FDB");
+        assert_eq!(template.reflected().to_string(), "BA/DC/FE");
+    }
+
+    #[test]
+    fn given_symmetric_template_variants_are_distinct() {
+        let template = Template::parse("M.M/.A./M.M").unwrap();
+
+        assert_eq!(template.variants(Symmetry::RotationsAndReflections).len(), 1);
+        assert_eq!(Template::parse("XMAS").unwrap().variants(Symmetry::RotationsAndReflections).len(), 4);
+        assert_eq!(Template::parse("XM/A.").unwrap().variants(Symmetry::RotationsAndReflections).len(), 8);
+    }
+
+    #[test]
+    fn given_ragged_template_parse_fails() {
+        assert_eq!(Template::parse("M.S/.A/M.S"), Err(TemplateError::RaggedRow { row: 2 }));
+        assert_eq!(Template::parse(" "), Err(TemplateError::Empty));
+    }
+}
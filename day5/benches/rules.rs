@@ -0,0 +1,59 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use day5::get_sum_of_correct_page_lists;
+use day5::rule_set::parse_manual;
+
+/// Rules between random pairs of pages following one hidden order, then updates of 5 to 25 of those pages.
+/// Every pair of pages within an update gets a rule, as in the puzzle, so each update has one correct order.
+/// Seeded, so runs compare.
+fn generate_input(rule_count: usize, update_count: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(2024);
+    let mut pages: Vec<u32> = (10..10 + rule_count as u32 / 10).collect();
+    pages.shuffle(&mut rng);
+
+    let mut rules = Vec::new();
+    let mut updates = Vec::new();
+    while rules.len() < rule_count {
+        let length = rng.gen_range(5..=25);
+        let mut update: Vec<usize> = rand::seq::index::sample(&mut rng, pages.len(), length).into_vec();
+        update.sort();
+        for i in 0..update.len() {
+            for j in i + 1..update.len() {
+                rules.push(format!("{}|{}", pages[update[i]], pages[update[j]]));
+            }
+        }
+        update.shuffle(&mut rng);
+        updates.push(update.iter().map(|index| pages[*index].to_string()).collect::<Vec<String>>().join(","));
+    }
+    while updates.len() < update_count {
+        updates.push(updates[rng.gen_range(0..updates.len())].clone());
+    }
+
+    return rules.join("\n") + "\n\n" + &updates[..update_count].join("\n");
+}
+
+fn bench_rules(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rules");
+    group.sample_size(10);
+
+    // Every update is checked against every rule, the larger input would take minutes.
+    let input = generate_input(1_000, 100);
+    group.bench_with_input(BenchmarkId::new("page_orders", "1000x100"), &input, |b, input| {
+        b.iter(|| get_sum_of_correct_page_lists(black_box(input)))
+    });
+
+    for (rule_count, update_count) in [(1_000, 100), (10_000, 10_000)] {
+        let input = generate_input(rule_count, update_count);
+        group.bench_with_input(BenchmarkId::new("rule_set", format!("{}x{}", rule_count, update_count)), &input, |b, input| {
+            b.iter(|| parse_manual(black_box(input)).unwrap().sum_middle_pages().unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_rules);
+criterion_main!(benches);
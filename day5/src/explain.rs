@@ -0,0 +1,117 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::rule_set::Manual;
+use crate::CycleError;
+
+/// The rule `before|after` is broken: `after` is at `after_position` in the update, before `before` at `before_position`.
+/// Positions count from 0.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct RuleViolation {
+    pub before: u32,
+    pub after: u32,
+    pub before_position: usize,
+    pub after_position: usize
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}|{} is broken, {} is at position {} and {} at position {}",
+            self.before, self.after, self.after, self.after_position, self.before, self.before_position);
+    }
+}
+
+/// An update out of order, every rule it breaks and the order that breaks none.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct UpdateExplanation {
+    pub line: usize,
+    pub pages: Vec<u32>,
+    pub violations: Vec<RuleViolation>,
+    pub corrected: Vec<u32>
+}
+
+/// Only the updates that are out of order are explained, in the order of the input.
+pub fn explain_updates(manual: &Manual) -> Result<Vec<UpdateExplanation>, CycleError> {
+    let mut explanations = Vec::new();
+
+    for (update, line) in manual.updates.iter().zip(&manual.update_lines) {
+        let mut violations = Vec::new();
+        for i in 0..update.len() {
+            for j in i + 1..update.len() {
+                if manual.rules.must_precede(update[j], update[i]) {
+                    violations.push(RuleViolation { before: update[j], after: update[i], before_position: j, after_position: i });
+                }
+            }
+        }
+        if violations.is_empty() {
+            continue;
+        }
+
+        explanations.push(UpdateExplanation { line: *line, pages: update.clone(), violations, corrected: manual.rules.sort(update)? });
+    }
+
+    return Ok(explanations);
+}
+
+fn join_pages(pages: &Vec<u32>) -> String {
+    return pages.iter().map(|page| page.to_string()).collect::<Vec<String>>().join(",");
+}
+
+/// For each update, its line and pages, one line per broken rule, then the corrected order.
+pub fn format_explanations(explanations: &Vec<UpdateExplanation>) -> String {
+    let mut listing = String::new();
+
+    for explanation in explanations {
+        listing += &format!("{:>5}  {}\n", explanation.line, join_pages(&explanation.pages));
+        for violation in &explanation.violations {
+            listing += &format!("       {}\n", violation);
+        }
+        listing += &format!("       corrected: {}\n\n", join_pages(&explanation.corrected));
+    }
+
+    listing += &format!("{} updates are out of order", explanations.len());
+    return listing;
+}
+
+pub fn explanations_to_json(explanations: &Vec<UpdateExplanation>) -> String {
+
+    return serde_json::to_string_pretty(explanations).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rule_set::parse_manual;
+    use crate::fixtures::EXAMPLE;
+
+    #[test]
+    fn given_example_incorrect_updates_are_explained() {
+        let manual = parse_manual(EXAMPLE).unwrap();
+
+        let result = explain_updates(&manual).unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0], UpdateExplanation {
+            line: 26,
+            pages: vec![75, 97, 47, 61, 53],
+            violations: vec![RuleViolation { before: 97, after: 75, before_position: 1, after_position: 0 }],
+            corrected: vec![97, 75, 47, 61, 53]
+        });
+        assert_eq!(result[2].violations.len(), 4);
+        assert_eq!(result[2].corrected, [97, 75, 47, 29, 13]);
+    }
+
+    #[test]
+    fn given_example_listing_shows_rules_and_corrected_order() {
+        let manual = parse_manual(EXAMPLE).unwrap();
+
+        let result = format_explanations(&explain_updates(&manual).unwrap());
+
+        assert!(result.contains("   27  61,13,29
+       29|13 is broken, 13 is at position 1 and 29 at position 2
+       corrected: 61,29,13
+"));
+        assert!(result.ends_with("3 updates are out of order"));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::rule_set::RuleSet;
+
+/// An edge of the rule graph, `before` pointing to `after`. It is broken when the update it was taken from has them the other way round.
+struct Edge {
+    before: u32,
+    after: u32,
+    is_broken: bool
+}
+
+/// Every rule, or with an update only the rules between its pages, which then also lists its pages as nodes
+/// so that pages no rule mentions are drawn too.
+fn collect_graph(rules: &RuleSet, update: Option<&[u32]>) -> (Vec<u32>, Vec<Edge>) {
+    let pages: Vec<u32> = match update {
+        Some(update) => update.to_vec(),
+        None => Vec::new()
+    };
+
+    let mut edges = Vec::new();
+    for (before, after) in rules.pairs() {
+        let is_broken = match update {
+            None => false,
+            Some(update) => match (update.iter().position(|page| *page == before), update.iter().position(|page| *page == after)) {
+                (Some(before_position), Some(after_position)) => after_position < before_position,
+                _ => continue
+            }
+        };
+        edges.push(Edge { before, after, is_broken });
+    }
+
+    return (pages, edges);
+}
+
+/// Graphviz, with broken rules in bold red.
+pub fn rules_to_dot(rules: &RuleSet, update: Option<&[u32]>) -> String {
+    let (pages, edges) = collect_graph(rules, update);
+
+    let mut output = String::from("digraph rules {\n");
+    for page in pages {
+        output += &format!("    {};\n", page);
+    }
+    for edge in edges {
+        let style = if edge.is_broken { " [color=red, penwidth=2]" } else { "" };
+        output += &format!("    {} -> {}{};\n", edge.before, edge.after, style);
+    }
+    output += "}\n";
+    return output;
+}
+
+/// A Mermaid flowchart, with broken rules in bold red. Nodes are named `p` and their page, since Mermaid ids are words.
+pub fn rules_to_mermaid(rules: &RuleSet, update: Option<&[u32]>) -> String {
+    let (pages, edges) = collect_graph(rules, update);
+
+    let mut output = String::from("flowchart LR\n");
+    for page in pages {
+        output += &format!("    p{}[{}]\n", page, page);
+    }
+    let mut broken_links = Vec::new();
+    for (i, edge) in edges.iter().enumerate() {
+        output += &format!("    p{}[{}] --> p{}[{}]\n", edge.before, edge.before, edge.after, edge.after);
+        if edge.is_broken {
+            broken_links.push(i.to_string());
+        }
+    }
+    if !broken_links.is_empty() {
+        output += &format!("    linkStyle {} stroke:red,stroke-width:2px\n", broken_links.join(","));
+    }
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rule_set::parse_manual;
+
+    const RULES: &str = "47|53
+97|75
+75|47
+53|29
+
+75,97,47";
+
+    #[test]
+    fn given_rules_dot_has_every_edge() {
+        let manual = parse_manual(RULES).unwrap();
+
+        let result = rules_to_dot(&manual.rules, None);
+
+        assert_eq!(result, "digraph rules {
+    47 -> 53;
+    53 -> 29;
+    75 -> 47;
+    97 -> 75;
+}
+");
+    }
+
+    #[test]
+    fn given_update_dot_has_its_pages_and_broken_rules_in_red() {
+        let manual = parse_manual(RULES).unwrap();
+
+        let result = rules_to_dot(&manual.rules, Some(&manual.updates[0]));
+
+        assert_eq!(result, "digraph rules {
+    75;
+    97;
+    47;
+    75 -> 47;
+    97 -> 75 [color=red, penwidth=2];
+}
+");
+    }
+
+    #[test]
+    fn given_update_mermaid_styles_broken_links() {
+        let manual = parse_manual(RULES).unwrap();
+
+        let result = rules_to_mermaid(&manual.rules, Some(&[97, 47, 75]));
+
+        assert_eq!(result, "flowchart LR
+    p97[97]
+    p47[47]
+    p75[75]
+    p75[75] --> p47[47]
+    p97[97] --> p75[75]
+    linkStyle 0 stroke:red,stroke-width:2px
+");
+    }
+}
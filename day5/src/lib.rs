@@ -1,16 +1,49 @@
-#[derive(Clone)]
-struct PageOrder {
-    page_before: String,
-    page_after: String
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+pub mod explain;
+#[cfg(test)]
+mod fixtures;
+pub mod graph;
+pub mod rule_set;
+
+/// A rule `page_before|page_after`: when an update has both pages, `page_before` comes first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageOrder {
+    pub page_before: String,
+    pub page_after: String
+}
+
+impl fmt::Display for PageOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}|{}", self.page_before, self.page_after);
+    }
+}
+
+/// The rules between the pages of an update go round in a circle, so no order satisfies all of them.
+/// `rules` is one such circle, each rule's `page_after` being the next one's `page_before`.
+#[derive(Debug, PartialEq)]
+pub struct CycleError {
+    pub rules: Vec<PageOrder>
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rules: Vec<String> = self.rules.iter().map(|rule| rule.to_string()).collect();
+        return write!(f, "The rules {} form a cycle", rules.join(", "));
+    }
 }
 
-fn read_page_numbers(line: &str) -> PageOrder {
+impl std::error::Error for CycleError {}
+
+pub fn read_page_numbers(line: &str) -> PageOrder {
     let mut numbers_in_line = line.split("|");
     return PageOrder { page_before : String::from(numbers_in_line.next().unwrap()), 
         page_after : String::from(numbers_in_line.next().unwrap()) }
 }
 
-fn read_page_number_list(line: &str) -> Vec<&str> {
+pub fn read_page_number_list(line: &str) -> Vec<&str> {
     let mut numbers_vector = Vec::new();
     let mut numbers_in_line = line.split(",");
 
@@ -25,20 +58,27 @@ fn read_page_number_list(line: &str) -> Vec<&str> {
     }
 }
 
-fn get_middle_page_number(line: &str) -> i32 {
+pub fn get_middle_page_number(line: &str) -> i32 {
     let numbers = read_page_number_list(line);
     let middle_index = (numbers.len() - 1) / 2;
 
     return numbers[middle_index].parse::<i32>().unwrap();
 }
 
-fn get_middle_page_number_vec(line: Vec<&str>) -> i32 {
+pub fn get_middle_page_number_vec(line: Vec<&str>) -> i32 {
     let middle_index = (line.len() - 1) / 2;
 
     return line[middle_index].parse::<i32>().unwrap();
 }
 
-fn get_sum_of_correct_page_lists(input: &str) -> i32 {
+/// Part 2, the middle pages of the updates once sorted.
+/// Panics when the rules of an update form a cycle, `get_sums_of_page_lists` reports it instead.
+pub fn get_sum_of_correct_page_lists(input: &str) -> i32 {
+    return get_sums_of_page_lists(input).unwrap().1;
+}
+
+/// The sums of the middle pages of the updates already in order (part 1), then of the other updates once sorted (part 2).
+pub fn get_sums_of_page_lists(input: &str) -> Result<(i32, i32), CycleError> {
     let mut rule_mode = true;
     let mut page_order_rules = Vec::new();
     let mut number_list_vector = Vec::new();
@@ -56,26 +96,28 @@ fn get_sum_of_correct_page_lists(input: &str) -> i32 {
         }
     }
 
+    let mut correct_sum = 0;
     let mut sum = 0;
     let mut incorrectly_ordered_updates = Vec::new();
 
     //println!("Number list : {:?}", number_list_vector);
     for number_list in number_list_vector {
         if !test_numbers_for_rules(page_order_rules.clone(), number_list.clone()) {
-            //sum += get_middle_page_number_vec(number_list)
             incorrectly_ordered_updates.push(number_list);
+        } else {
+            correct_sum += get_middle_page_number_vec(number_list);
         }
     }
 
-    for mut update in incorrectly_ordered_updates {
-        let mut sorted_update = bubble_sort_pages(page_order_rules.clone(), &mut update);
-        sum += get_middle_page_number_vec(sorted_update.to_vec());
+    for update in incorrectly_ordered_updates {
+        let sorted_update = sort_pages(&page_order_rules, &update)?;
+        sum += get_middle_page_number_vec(sorted_update);
     }
 
-    return sum;
+    return Ok((correct_sum, sum));
 }
 
-fn test_numbers_for_rules(rules: Vec<PageOrder>, number_list: Vec<&str>) -> bool {
+pub fn test_numbers_for_rules(rules: Vec<PageOrder>, number_list: Vec<&str>) -> bool {
     for i in 0..number_list.len()-1 {
         for j in i+1..number_list.len() {
             let first_number = number_list[i];
@@ -91,23 +133,107 @@ fn test_numbers_for_rules(rules: Vec<PageOrder>, number_list: Vec<&str>) -> bool
     return true;
 }
 
-fn bubble_sort_pages<'a>(rules: Vec<PageOrder>, number_list: &'a mut Vec<&'a str>) -> &'a mut Vec<&'a str> {
-    for i in 0..number_list.len()-1 {
-        for j in i+1..number_list.len() {
-            for page_order in rules.iter() {
-                if page_order.page_after == number_list[i] && page_order.page_before == number_list[j] {
-                    number_list.swap(i, j);
+/// Orders the pages of an update so that every rule between two of its pages holds, by topological sort
+/// of the rules restricted to those pages. Pages no rule orders keep the order they had in the update.
+pub fn sort_pages<'a>(rules: &[PageOrder], number_list: &[&'a str]) -> Result<Vec<&'a str>, CycleError> {
+    let mut positions: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, number) in number_list.iter().enumerate() {
+        positions.entry(number).or_default().push(i);
+    }
+
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); number_list.len()];
+    for rule in rules {
+        if let (Some(befores), Some(afters)) = (positions.get(rule.page_before.as_str()), positions.get(rule.page_after.as_str())) {
+            for before in befores {
+                for after in afters {
+                    successors[*before].push(*after);
                 }
             }
         }
     }
 
-    return number_list;
+    return match order_positions(&successors) {
+        Ok(order) => Ok(order.iter().map(|position| number_list[*position]).collect()),
+        Err(cycle) => Err(CycleError { rules: cycle.iter().map(|(before, after)| PageOrder {
+            page_before: String::from(number_list[*before]),
+            page_after: String::from(number_list[*after])
+        }).collect() })
+    };
+}
+
+/// Topological sort of the positions of an update, given the positions that must come after each one.
+/// Among the positions ready to go, the one earliest in the update goes first.
+/// On failure, returns the edges of a cycle, each ending where the next one starts.
+fn order_positions(successors: &[Vec<usize>]) -> Result<Vec<usize>, Vec<(usize, usize)>> {
+    let mut predecessor_counts = vec![0; successors.len()];
+    for position_successors in successors {
+        for successor in position_successors {
+            predecessor_counts[*successor] += 1;
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..successors.len())
+        .filter(|i| predecessor_counts[*i] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = Vec::new();
+    while let Some(Reverse(position)) = ready.pop() {
+        order.push(position);
+        for successor in &successors[position] {
+            predecessor_counts[*successor] -= 1;
+            if predecessor_counts[*successor] == 0 {
+                ready.push(Reverse(*successor));
+            }
+        }
+    }
+
+    if order.len() < successors.len() {
+        return Err(find_cycle(successors, &predecessor_counts));
+    }
+    return Ok(order);
+}
+
+/// Every position left with predecessors after the sort has one among the other positions left,
+/// so following them backwards from any of those eventually comes back to a position already seen.
+fn find_cycle(successors: &[Vec<usize>], predecessor_counts: &[usize]) -> Vec<(usize, usize)> {
+    let is_left = |position: usize| predecessor_counts[position] > 0;
+    let mut predecessors: Vec<Option<usize>> = vec![None; successors.len()];
+    for (position, position_successors) in successors.iter().enumerate() {
+        if !is_left(position) {
+            continue;
+        }
+        for successor in position_successors {
+            if is_left(*successor) {
+                predecessors[*successor] = Some(position);
+            }
+        }
+    }
+
+    let mut visited = vec![false; successors.len()];
+    let mut position = (0..successors.len()).find(|position| is_left(*position)).unwrap();
+    while !visited[position] {
+        visited[position] = true;
+        position = predecessors[position].unwrap();
+    }
+
+    let start = position;
+    let mut cycle = Vec::new();
+    loop {
+        let predecessor = predecessors[position].unwrap();
+        cycle.push((predecessor, position));
+        position = predecessor;
+        if position == start {
+            break;
+        }
+    }
+    cycle.reverse();
+    return cycle;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::EXAMPLE;
 
     #[test]
     fn given_page_numbers_should_be_read_correctly() {
@@ -186,7 +312,7 @@ mod tests {
         numbers_input.push("3");
         numbers_input.push("2");
 
-        let result = bubble_sort_pages(rules_input, &mut numbers_input);
+        let result = sort_pages(&rules_input, &numbers_input).unwrap();
 
         let mut numbers_expected = Vec::new();
         numbers_expected.push("1");
@@ -195,7 +321,31 @@ mod tests {
         numbers_expected.push("4");
         numbers_expected.push("5");
 
-        assert_eq!(result, &mut numbers_expected);
+        assert_eq!(result, numbers_expected);
+    }
+
+    #[test]
+    fn given_pages_without_rules_then_they_keep_their_order() {
+        let rules_input = vec![PageOrder { page_before : String::from("3"), page_after : String::from("1") }];
+
+        let result = sort_pages(&rules_input, &vec!["1", "2", "3", "4"]).unwrap();
+
+        assert_eq!(result, ["2", "3", "1", "4"]);
+    }
+
+    #[test]
+    fn given_rules_in_a_circle_then_the_cycle_is_reported() {
+        let rules_input = vec![
+            PageOrder { page_before : String::from("1"), page_after : String::from("2") },
+            PageOrder { page_before : String::from("2"), page_after : String::from("3") },
+            PageOrder { page_before : String::from("3"), page_after : String::from("1") },
+            PageOrder { page_before : String::from("4"), page_after : String::from("1") }
+        ];
+
+        let result = sort_pages(&rules_input, &vec!["4", "1", "2", "3"]);
+
+        let error = result.unwrap_err();
+        assert_eq!(error.to_string(), "The rules 1|2, 2|3, 3|1 form a cycle");
     }
 
     #[test]
@@ -260,4 +410,24 @@ mod tests {
 
         assert_eq!(result, 123);
     }
+
+    #[test]
+    fn given_example_sums_are_given_for_both_parts() {
+        let result = get_sums_of_page_lists(EXAMPLE);
+
+        assert_eq!(result, Ok((143, 123)));
+    }
+
+    #[test]
+    fn given_rules_in_a_circle_the_sum_fails() {
+        let input = "1|2
+2|3
+3|1
+
+3,2,1";
+
+        let result = get_sums_of_page_lists(input);
+
+        assert_eq!(result.unwrap_err().to_string(), "The rules 3|1, 1|2, 2|3 form a cycle");
+    }
 }
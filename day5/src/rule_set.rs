@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::{order_positions, CycleError, PageOrder};
+
+#[derive(Debug, PartialEq)]
+pub enum ManualError {
+    InvalidRule(String),
+    InvalidPage(String)
+}
+
+impl fmt::Display for ManualError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            ManualError::InvalidRule(line) => write!(f, "Invalid rule: {}", line),
+            ManualError::InvalidPage(page) => write!(f, "Invalid page number: {}", page)
+        };
+    }
+}
+
+impl std::error::Error for ManualError {}
+
+fn parse_page(page: &str) -> Result<u32, ManualError> {
+    return page.trim().parse().map_err(|_| ManualError::InvalidPage(String::from(page)));
+}
+
+/// The page ordering rules with their pages as numbers, in a set of `(before, after)` pairs,
+/// so checking whether two pages are ordered is one lookup instead of a pass over every rule.
+#[derive(Clone, Debug)]
+pub struct RuleSet {
+    ordered_pairs: HashSet<(u32, u32)>
+}
+
+impl RuleSet {
+
+    pub fn new(rules: &Vec<PageOrder>) -> Result<RuleSet, ManualError> {
+        let mut ordered_pairs = HashSet::new();
+        for rule in rules {
+            ordered_pairs.insert((parse_page(&rule.page_before)?, parse_page(&rule.page_after)?));
+        }
+        return Ok(RuleSet { ordered_pairs });
+    }
+
+    pub fn len(&self) -> usize {
+        return self.ordered_pairs.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.ordered_pairs.is_empty();
+    }
+
+    /// Every rule as `(before, after)`, sorted.
+    pub fn pairs(&self) -> Vec<(u32, u32)> {
+        let mut pairs: Vec<(u32, u32)> = self.ordered_pairs.iter().copied().collect();
+        pairs.sort();
+        return pairs;
+    }
+
+    /// Whether a rule `before|after` exists.
+    pub fn must_precede(&self, before: u32, after: u32) -> bool {
+        return self.ordered_pairs.contains(&(before, after));
+    }
+
+    /// No page of the update comes after a page a rule puts after it.
+    pub fn is_ordered(&self, update: &[u32]) -> bool {
+        for i in 0..update.len() {
+            for j in i + 1..update.len() {
+                if self.must_precede(update[j], update[i]) {
+                    return false;
+                }
+            }
+        }
+        return true;
+    }
+
+    /// Same order as `sort_pages`. A rule between a page and itself is a cycle, as there.
+    pub fn sort(&self, update: &[u32]) -> Result<Vec<u32>, CycleError> {
+        let mut successors = vec![Vec::new(); update.len()];
+        for i in 0..update.len() {
+            for j in 0..update.len() {
+                if self.must_precede(update[i], update[j]) {
+                    successors[i].push(j);
+                }
+            }
+        }
+
+        return match order_positions(&successors) {
+            Ok(order) => Ok(order.iter().map(|position| update[*position]).collect()),
+            Err(cycle) => Err(CycleError { rules: cycle.iter().map(|(before, after)| PageOrder {
+                page_before: update[*before].to_string(),
+                page_after: update[*after].to_string()
+            }).collect() })
+        };
+    }
+}
+
+/// The rules, then after an empty line the updates, as in the puzzle input.
+pub struct Manual {
+    pub rules: RuleSet,
+    pub updates: Vec<Vec<u32>>,
+    /// The line of each update in the input, counting from 1.
+    pub update_lines: Vec<usize>
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MiddlePageSums {
+    /// Part 1, the updates already in order.
+    pub correct: u64,
+    /// Part 2, the other updates once sorted.
+    pub corrected: u64
+}
+
+pub fn parse_manual(input: &str) -> Result<Manual, ManualError> {
+    let mut rules = Vec::new();
+    let mut updates = Vec::new();
+    let mut update_lines = Vec::new();
+    let mut rule_mode = true;
+
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            rule_mode = false;
+            continue;
+        }
+        if rule_mode {
+            let (page_before, page_after) = line.split_once('|').ok_or(ManualError::InvalidRule(String::from(line)))?;
+            rules.push(PageOrder { page_before: String::from(page_before), page_after: String::from(page_after) });
+        } else {
+            updates.push(line.split(',').map(parse_page).collect::<Result<Vec<u32>, ManualError>>()?);
+            update_lines.push(index + 1);
+        }
+    }
+
+    return Ok(Manual { rules: RuleSet::new(&rules)?, updates, update_lines });
+}
+
+impl Manual {
+
+    pub fn sum_middle_pages(&self) -> Result<MiddlePageSums, CycleError> {
+        let mut sums = MiddlePageSums { correct: 0, corrected: 0 };
+        for update in &self.updates {
+            if self.rules.is_ordered(update) {
+                sums.correct += get_middle_page(update) as u64;
+            } else {
+                sums.corrected += get_middle_page(&self.rules.sort(update)?) as u64;
+            }
+        }
+        return Ok(sums);
+    }
+}
+
+fn get_middle_page(update: &[u32]) -> u32 {
+    return update[(update.len() - 1) / 2];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_sum_of_correct_page_lists, read_page_numbers, sort_pages};
+    use crate::fixtures::EXAMPLE;
+
+    #[test]
+    fn given_example_middle_pages_sum_for_both_parts() {
+        let manual = parse_manual(EXAMPLE).unwrap();
+
+        let result = manual.sum_middle_pages().unwrap();
+
+        assert_eq!(result, MiddlePageSums { correct: 143, corrected: 123 });
+        assert_eq!(result.corrected, get_sum_of_correct_page_lists(EXAMPLE) as u64);
+    }
+
+    #[test]
+    fn given_example_updates_are_sorted_like_the_puzzle() {
+        let manual = parse_manual(EXAMPLE).unwrap();
+
+        assert_eq!(manual.rules.len(), 21);
+        assert_eq!(manual.rules.sort(&[97, 13, 75, 29, 47]).unwrap(), [97, 75, 47, 29, 13]);
+        assert_eq!(manual.rules.is_ordered(&[75, 47, 61, 53, 29]), true);
+        assert_eq!(manual.rules.is_ordered(&[61, 13, 29]), false);
+    }
+
+    #[test]
+    fn given_rule_between_a_page_and_itself_sorting_fails_like_sort_pages() {
+        let manual = parse_manual("5|5\n5|7\n\n7,5").unwrap();
+        let rules = vec![read_page_numbers("5|5"), read_page_numbers("5|7")];
+
+        let result = manual.rules.sort(&manual.updates[0]);
+
+        assert_eq!(result.unwrap_err().to_string(), "The rules 5|5 form a cycle");
+        assert_eq!(sort_pages(&rules, &vec!["7", "5"]).unwrap_err().to_string(), "The rules 5|5 form a cycle");
+    }
+
+    #[test]
+    fn given_bad_page_parsing_fails() {
+        assert_eq!(parse_manual("1|x\n\n1").err(), Some(ManualError::InvalidPage(String::from("x"))));
+        assert_eq!(parse_manual("12\n\n1").err(), Some(ManualError::InvalidRule(String::from("12"))));
+    }
+}
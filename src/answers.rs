@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The accepted answers of one event, read from a file like
+///
+/// ```toml
+/// [day1]
+/// part1 = 11
+/// part2 = "31"
+/// ```
+///
+/// Numbers and strings are both accepted since some puzzles have text answers.
+pub struct Answers {
+    answers: HashMap<(u8, u8), String>
+}
+
+impl Answers {
+
+    /// A missing file means no answer has been recorded yet.
+    pub fn load(path: &Path) -> Result<Answers, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Could not read {}: {}", path.display(), e))
+        };
+
+        Answers::parse(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+    }
+
+    fn parse(content: &str) -> Result<Answers, String> {
+        let table: HashMap<String, HashMap<String, toml::Value>> = toml::from_str(content).map_err(|e| e.to_string())?;
+
+        let mut answers = HashMap::new();
+        for (day_key, parts) in table {
+            let day = parse_key(&day_key, "day")?;
+            for (part_key, value) in parts {
+                let part = parse_key(&part_key, "part")?;
+                let value = match value {
+                    toml::Value::String(text) => text,
+                    other => other.to_string()
+                };
+                answers.insert((day, part), value);
+            }
+        }
+
+        Ok(Answers { answers })
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+}
+
+fn parse_key(key: &str, prefix: &str) -> Result<u8, String> {
+    key.strip_prefix(prefix)
+        .and_then(|number| number.parse().ok())
+        .ok_or(format!("Invalid key: {} (expected {}<number>)", key, prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_numbers_and_strings_answers_are_read_as_text() {
+        let answers = Answers::parse("[day1]\npart1 = 11\npart2 = \"31\"\n\n[day3]\npart2 = 48").unwrap();
+
+        assert_eq!(answers.get(1, 1), Some("11"));
+        assert_eq!(answers.get(1, 2), Some("31"));
+        assert_eq!(answers.get(3, 2), Some("48"));
+        assert_eq!(answers.get(2, 1), None);
+    }
+
+    #[test]
+    fn given_bad_key_answers_are_rejected() {
+        let result = Answers::parse("[first]\npart1 = 11");
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+const DEFAULT_CONFIG_FILE: &str = "aoc.toml";
+
+/// How answers are printed by the runner commands.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json
+}
+
+impl OutputFormat {
+    pub fn parse(value: &str) -> Result<OutputFormat, String> {
+        match value {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("Unknown output format: {} (expected text or json)", value))
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json")
+        }
+    }
+}
+
+/// Everything in `aoc.toml` is optional, missing keys fall back to the defaults of `Config`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    year: Option<u16>,
+    input_dir: Option<PathBuf>,
+    answers_dir: Option<PathBuf>,
+    cache_dir: Option<PathBuf>,
+    base_url: Option<String>,
+    session_file: Option<PathBuf>,
+    output_format: Option<String>,
+    strategies: HashMap<String, HashMap<String, String>>,
+    rules: HashMap<String, HashMap<String, PathBuf>>
+}
+
+/// The settings shared by every runner command.
+/// Values come from `aoc.toml` (or the file named by `AOC_CONFIG`), then `AOC_*` environment variables override them.
+/// `year` is the event used when `--year` is not given, inputs and answers are stored per year.
+#[derive(Debug)]
+pub struct Config {
+    pub year: u16,
+    pub input_dir: PathBuf,
+    pub answers_dir: PathBuf,
+    pub cache_dir: PathBuf,
+    pub base_url: String,
+    pub session_file: PathBuf,
+    pub output_format: OutputFormat,
+    /// The directory holding the config file, or the current directory without one. The `dayN` crates are found from it.
+    pub root_dir: PathBuf,
+    strategies: HashMap<(u16, u8), String>,
+    rules: HashMap<(u16, u8), PathBuf>
+}
+
+impl Config {
+
+    pub fn load() -> Result<Config, String> {
+        let path = env::var("AOC_CONFIG").map(PathBuf::from).ok();
+        let file = match &path {
+            Some(path) => read_config_file(path)?,
+            None => read_config_file_if_present(&PathBuf::from(DEFAULT_CONFIG_FILE))?
+        };
+        let root_dir = path.as_deref()
+            .and_then(Path::parent)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(PathBuf::from)
+            .unwrap_or(PathBuf::from("."));
+
+        Config::from_file(file, root_dir, &env::vars().collect())
+    }
+
+    /// `environment` is passed in so the override order can be tested without touching the process environment.
+    fn from_file(file: ConfigFile, root_dir: PathBuf, environment: &HashMap<String, String>) -> Result<Config, String> {
+        let get_env = |name: &str| environment.get(name).cloned();
+        let year = match get_env("AOC_YEAR") {
+            Some(year) => year.parse().map_err(|_| format!("Invalid AOC_YEAR: {}", year))?,
+            None => file.year.unwrap_or(2024)
+        };
+        let output_format = match get_env("AOC_OUTPUT_FORMAT").or(file.output_format) {
+            Some(format) => OutputFormat::parse(&format)?,
+            None => OutputFormat::Text
+        };
+
+        let strategies = collect_day_settings("strategies", file.strategies, "AOC_STRATEGY_", year, environment)?;
+        let rules = collect_day_settings("rules", file.rules, "AOC_RULES_", year, environment)?;
+
+        Ok(Config {
+            year,
+            input_dir: get_env("AOC_INPUT_DIR").map(PathBuf::from).or(file.input_dir).unwrap_or(PathBuf::from("inputs")),
+            answers_dir: get_env("AOC_ANSWERS_DIR").map(PathBuf::from).or(file.answers_dir).unwrap_or(PathBuf::from("answers")),
+            cache_dir: get_env("AOC_CACHE_DIR").map(PathBuf::from).or(file.cache_dir).unwrap_or(PathBuf::from(".aoc-cache")),
+            base_url: get_env("AOC_BASE_URL").or(file.base_url).unwrap_or(String::from("https://adventofcode.com")),
+            session_file: get_env("AOC_SESSION_FILE").map(PathBuf::from).or(file.session_file).unwrap_or(PathBuf::from(".aoc-session")),
+            output_format,
+            root_dir,
+            strategies,
+            rules
+        })
+    }
+
+    /// The input used by `run` when no input is given on the command line.
+    pub fn input_path(&self, year: u16, day: u8) -> PathBuf {
+        self.input_dir.join(year.to_string()).join(format!("day{:02}.txt", day))
+    }
+
+    /// The verified answers of one event, see `answers::Answers`.
+    pub fn answers_path(&self, year: u16) -> PathBuf {
+        self.answers_dir.join(format!("{}.toml", year))
+    }
+
+    pub fn strategy_for_day(&self, year: u16, day: u8) -> Option<&str> {
+        self.strategies.get(&(year, day)).map(String::as_str)
+    }
+
+    /// The rule file of a day that checks its input against rules read at runtime, such as day 2 of 2024.
+    pub fn rules_for_day(&self, year: u16, day: u8) -> Option<&Path> {
+        self.rules.get(&(year, day)).map(PathBuf::as_path)
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "year = {}", self.year)?;
+        writeln!(f, "input_dir = {}", self.input_dir.display())?;
+        writeln!(f, "answers_dir = {}", self.answers_dir.display())?;
+        writeln!(f, "cache_dir = {}", self.cache_dir.display())?;
+        writeln!(f, "base_url = {}", self.base_url)?;
+        writeln!(f, "session_file = {}", self.session_file.display())?;
+        write!(f, "output_format = {}", self.output_format)?;
+
+        let mut keys: Vec<&(u16, u8)> = self.strategies.keys().collect();
+        keys.sort();
+        for key in keys {
+            write!(f, "\nstrategies.{}.day{} = {}", key.0, key.1, self.strategies[key])?;
+        }
+
+        let mut keys: Vec<&(u16, u8)> = self.rules.keys().collect();
+        keys.sort();
+        for key in keys {
+            write!(f, "\nrules.{}.day{} = {}", key.0, key.1, self.rules[key].display())?;
+        }
+        Ok(())
+    }
+}
+
+fn read_config_file(path: &PathBuf) -> Result<ConfigFile, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+    toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+fn read_config_file_if_present(path: &PathBuf) -> Result<ConfigFile, String> {
+    match fs::metadata(path) {
+        Ok(_) => read_config_file(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(ConfigFile::default()),
+        Err(e) => Err(format!("Could not read {}: {}", path.display(), e))
+    }
+}
+
+/// A setting per year and day: the `[<table>.<year>]` tables of the file, overridden by the `<prefix><year>_DAY<n>`
+/// environment variables. `<prefix>DAY<n>` is the older name, for the default year, and the name with the year wins over it.
+fn collect_day_settings<T: for<'a> From<&'a str>>(table_name: &str, table: HashMap<String, HashMap<String, T>>, prefix: &str, year: u16,
+                                                  environment: &HashMap<String, String>) -> Result<HashMap<(u16, u8), T>, String> {
+    let mut settings = HashMap::new();
+    for (year_key, days) in table {
+        let setting_year: u16 = year_key.parse().map_err(|_| format!("Invalid year: [{}.{}]", table_name, year_key))?;
+        for (day_key, setting) in days {
+            settings.insert((setting_year, parse_day_key(&day_key)?), setting);
+        }
+    }
+
+    let mut overrides: Vec<(bool, u16, u8, &String)> = Vec::new();
+    for (name, value) in environment {
+        let key = match name.strip_prefix(prefix) {
+            Some(key) => key,
+            None => continue
+        };
+        let invalid = || format!("Invalid {} (expected {}<year>_DAY<n> or {}DAY<n>)", name, prefix, prefix);
+        match key.split_once('_') {
+            Some((year_key, day_key)) => {
+                let setting_year = year_key.parse().map_err(|_| invalid())?;
+                overrides.push((true, setting_year, parse_day_key(&day_key.to_lowercase()).map_err(|_| invalid())?, value));
+            }
+            None => overrides.push((false, year, parse_day_key(&key.to_lowercase()).map_err(|_| invalid())?, value))
+        }
+    }
+    overrides.sort();
+    for (_, setting_year, day, value) in overrides {
+        settings.insert((setting_year, day), T::from(value));
+    }
+
+    Ok(settings)
+}
+
+/// Days are keyed `day1` to `day25` in a `[strategies.<year>]` or `[rules.<year>]` table.
+fn parse_day_key(key: &str) -> Result<u8, String> {
+    key.strip_prefix("day")
+        .and_then(|day| day.parse().ok())
+        .filter(|day| (1..=25).contains(day))
+        .ok_or(format!("Invalid strategy key: {} (expected day1 to day25)", key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn environment(variables: &[(&str, &str)]) -> HashMap<String, String> {
+        variables.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn given_empty_file_and_environment_defaults_are_used() {
+        let config = Config::from_file(ConfigFile::default(), PathBuf::from("."), &HashMap::new()).unwrap();
+
+        assert_eq!(config.year, 2024);
+        assert_eq!(config.input_path(2024, 3), PathBuf::from("inputs/2024/day03.txt"));
+        assert_eq!(config.answers_path(2023), PathBuf::from("answers/2023.toml"));
+        assert_eq!(config.output_format, OutputFormat::Text);
+        assert_eq!(config.strategy_for_day(2024, 1), None);
+    }
+
+    #[test]
+    fn given_file_and_environment_environment_wins() {
+        let file: ConfigFile = toml::from_str("
+input_dir = \"puzzles\"
+output_format = \"json\"
+
+[strategies.2024]
+day1 = \"naive\"
+day2 = \"naive\"
+
+[strategies.2023]
+day1 = \"older\"
+").unwrap();
+
+        let config = Config::from_file(file, PathBuf::from("."), &environment(&[
+            ("AOC_INPUT_DIR", "elsewhere"),
+            ("AOC_STRATEGY_2024_DAY2", "other")
+        ])).unwrap();
+
+        assert_eq!(config.input_dir, PathBuf::from("elsewhere"));
+        assert_eq!(config.output_format, OutputFormat::Json);
+        assert_eq!(config.strategy_for_day(2024, 1), Some("naive"));
+        assert_eq!(config.strategy_for_day(2024, 2), Some("other"));
+        assert_eq!(config.strategy_for_day(2023, 1), Some("older"));
+    }
+
+    #[test]
+    fn given_bad_strategy_key_loading_fails() {
+        let file: ConfigFile = toml::from_str("[strategies.2024]\nfirst = \"naive\"").unwrap();
+
+        let result = Config::from_file(file, PathBuf::from("."), &HashMap::new());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn given_strategy_variables_every_year_is_overridden() {
+        let file: ConfigFile = toml::from_str("year = 2023").unwrap();
+
+        let config = Config::from_file(file, PathBuf::from("."), &environment(&[
+            ("AOC_STRATEGY_2024_DAY1", "nested"),
+            ("AOC_STRATEGY_DAY3", "tokens"),
+            ("AOC_STRATEGY_DAY4", "words"),
+            ("AOC_STRATEGY_2023_DAY4", "template")
+        ])).unwrap();
+
+        assert_eq!(config.strategy_for_day(2024, 1), Some("nested"));
+        assert_eq!(config.strategy_for_day(2023, 3), Some("tokens"));
+        assert_eq!(config.strategy_for_day(2023, 4), Some("template"));
+        assert_eq!(config.strategy_for_day(2024, 3), None);
+    }
+
+    #[test]
+    fn given_rule_files_they_are_overridden_like_strategies() {
+        let file: ConfigFile = toml::from_str("
+[rules.2024]
+day2 = \"rules/day2.rules\"
+
+[rules.2023]
+day2 = \"rules/older.rules\"
+").unwrap();
+
+        let config = Config::from_file(file, PathBuf::from("."), &environment(&[
+            ("AOC_RULES_DAY2", "elsewhere.rules"),
+            ("AOC_RULES_2023_DAY5", "pages.rules")
+        ])).unwrap();
+
+        assert_eq!(config.rules_for_day(2024, 2), Some(Path::new("elsewhere.rules")));
+        assert_eq!(config.rules_for_day(2023, 2), Some(Path::new("rules/older.rules")));
+        assert_eq!(config.rules_for_day(2023, 5), Some(Path::new("pages.rules")));
+        assert_eq!(config.rules_for_day(2024, 1), None);
+    }
+
+    #[test]
+    fn given_bad_strategy_variable_loading_fails() {
+        let result = Config::from_file(ConfigFile::default(), PathBuf::from("."), &environment(&[("AOC_STRATEGY_DAY26", "nested")]));
+
+        assert!(result.is_err());
+    }
+}
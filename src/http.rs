@@ -0,0 +1,47 @@
+use std::fs;
+use std::io;
+use std::time::Duration;
+
+use crate::config::Config;
+
+/// The one client used to talk to the Advent of Code site, for inputs as well as leaderboards.
+/// `base_url` can point to a local stub, which is why the session is optional.
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    session: Option<String>
+}
+
+impl Client {
+
+    pub fn from_config(config: &Config) -> Result<Client, String> {
+        let session = match fs::read_to_string(&config.session_file) {
+            Ok(token) => Some(token.trim().to_string()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("Could not read {}: {}", config.session_file.display(), e))
+        };
+
+        Ok(Client {
+            agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(30)).build(),
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            session
+        })
+    }
+
+    /// `path` starts with a slash, e.g. `/2024/day/1/input`.
+    pub fn get(&self, path: &str) -> Result<String, String> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut request = self.agent.get(&url)
+            .set("User-Agent", concat!("aoc2024/", env!("CARGO_PKG_VERSION")));
+        if let Some(session) = &self.session {
+            request = request.set("Cookie", &format!("session={}", session));
+        }
+
+        let response = request.call().map_err(|e| match e {
+            ureq::Error::Status(401, _) | ureq::Error::Status(403, _) if self.session.is_none() => format!("{} needs a session token", url),
+            e => format!("Could not fetch {}", e)
+        })?;
+
+        response.into_string().map_err(|e| format!("Could not read {}: {}", url, e))
+    }
+}
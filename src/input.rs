@@ -0,0 +1,68 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::http::Client;
+
+/// Where a day reads its puzzle input from.
+/// A named pipe is opened like any other file and read as it is written to.
+pub enum Input {
+    Stdin,
+    File(PathBuf)
+}
+
+impl Input {
+
+    /// `-` means standard input, anything else is a path.
+    pub fn from_argument(argument: &str) -> Input {
+        if argument == "-" {
+            return Input::Stdin;
+        }
+        Input::File(PathBuf::from(argument))
+    }
+
+    pub fn open(&self) -> io::Result<Box<dyn BufRead>> {
+        match self {
+            Input::Stdin => Ok(Box::new(BufReader::new(io::stdin()))),
+            Input::File(path) => Ok(Box::new(BufReader::new(File::open(path)?)))
+        }
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Input::Stdin => write!(f, "stdin"),
+            Input::File(path) => write!(f, "{}", path.display())
+        }
+    }
+}
+
+/// The input of a day in the input directory. It is never downloaded here, see `fetch_input`.
+pub fn find_input(config: &Config, year: u16, day: u8) -> Result<Input, String> {
+    let path = config.input_path(year, day);
+    if !path.is_file() {
+        return Err(format!("{} is missing, download it with: aoc2024 fetch {} --year {}", path.display(), day, year));
+    }
+
+    Ok(Input::File(path))
+}
+
+/// Downloads the input of a day into the input directory, unless it is already there.
+/// Returns the path of the input and whether it was downloaded.
+pub fn fetch_input(config: &Config, year: u16, day: u8) -> Result<(PathBuf, bool), String> {
+    let path = config.input_path(year, day);
+    if path.is_file() {
+        return Ok((path, false));
+    }
+
+    let content = Client::from_config(config)?.get(&format!("/{}/day/{}/input", year, day))?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(|e| format!("Could not create {}: {}", directory.display(), e))?;
+    }
+    fs::write(&path, content).map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+
+    Ok((path, true))
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::http::Client;
+
+/// The site asks not to fetch a private leaderboard more than once every 15 minutes.
+const CACHE_LIFETIME: Duration = Duration::from_secs(15 * 60);
+
+/// The private leaderboard JSON, as downloaded from `/<year>/leaderboard/private/view/<id>.json`.
+/// Only the fields used here are read.
+#[derive(Debug, Deserialize)]
+pub struct Leaderboard {
+    pub event: String,
+    pub members: HashMap<String, Member>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    /// Keyed by day, then by part.
+    #[serde(default)]
+    pub completion_day_level: HashMap<String, HashMap<String, Star>>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Star {
+    pub get_star_ts: i64
+}
+
+impl Member {
+
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id)
+        }
+    }
+
+    fn star_timestamp(&self, day: u8, part: u8) -> Option<i64> {
+        self.completion_day_level.get(&day.to_string())?
+            .get(&part.to_string())
+            .map(|star| star.get_star_ts)
+    }
+
+    fn count_stars(&self) -> usize {
+        self.completion_day_level.values().map(|parts| parts.len()).sum()
+    }
+}
+
+pub fn parse_leaderboard(json: &str) -> Result<Leaderboard, String> {
+    serde_json::from_str(json).map_err(|e| format!("Invalid leaderboard: {}", e))
+}
+
+pub fn load_leaderboard(path: &Path) -> Result<Leaderboard, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+    parse_leaderboard(&json)
+}
+
+/// A leaderboard file names its own event, which has to be the year asked for.
+pub fn check_event(leaderboard: &Leaderboard, year: u16) -> Result<(), String> {
+    match leaderboard.event == year.to_string() {
+        true => Ok(()),
+        false => Err(format!("The leaderboard is for {}, not {}", leaderboard.event, year))
+    }
+}
+
+/// Fetches through the shared client, reusing the copy in the cache directory while it is recent enough.
+pub fn fetch_leaderboard(config: &Config, year: u16, id: &str) -> Result<Leaderboard, String> {
+    let cache_path = config.cache_dir.join(format!("leaderboard-{}-{}.json", year, id));
+    let is_recent = fs::metadata(&cache_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < CACHE_LIFETIME);
+    if is_recent {
+        return load_leaderboard(&cache_path);
+    }
+
+    let json = Client::from_config(config)?.get(&format!("/{}/leaderboard/private/view/{}.json", year, id))?;
+    let leaderboard = parse_leaderboard(&json)?;
+
+    fs::create_dir_all(&config.cache_dir).map_err(|e| format!("Could not create {}: {}", config.cache_dir.display(), e))?;
+    fs::write(&cache_path, json).map_err(|e| format!("Could not write {}: {}", cache_path.display(), e))?;
+
+    Ok(leaderboard)
+}
+
+/// Every star is worth as many points as there are members for the first to get it, one less for the second, and so on.
+pub fn compute_local_scores(leaderboard: &Leaderboard) -> HashMap<u64, usize> {
+    let member_count = leaderboard.members.len();
+    let mut scores: HashMap<u64, usize> = leaderboard.members.values().map(|member| (member.id, 0)).collect();
+
+    for day in 1..=25 {
+        for part in 1..=2 {
+            let mut finishers: Vec<(i64, u64)> = leaderboard.members.values()
+                .filter_map(|member| member.star_timestamp(day, part).map(|timestamp| (timestamp, member.id)))
+                .collect();
+            finishers.sort();
+
+            for (rank, (_, id)) in finishers.iter().enumerate() {
+                *scores.get_mut(id).unwrap() += member_count - rank;
+            }
+        }
+    }
+
+    scores
+}
+
+/// Puzzles unlock at midnight EST, which is 05:00 UTC.
+fn unlock_timestamp(year: i64, day: u8) -> i64 {
+    days_from_civil(year, 12, day as i64) * 86400 + 5 * 3600
+}
+
+/// Days between 1970-01-01 and the given date of the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+/// A star before its puzzle unlocked cannot come from the site, only from an edited file, so it counts as no time at all.
+fn format_duration(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let days = seconds / 86400;
+    let hours = seconds % 86400 / 3600;
+    let minutes = seconds % 3600 / 60;
+    let seconds = seconds % 60;
+
+    match days {
+        0 => format!("{}:{:02}:{:02}", hours, minutes, seconds),
+        _ => format!("{}d {:02}:{:02}:{:02}", days, hours, minutes, seconds)
+    }
+}
+
+fn ranked_members<'a>(leaderboard: &'a Leaderboard, scores: &HashMap<u64, usize>) -> Vec<&'a Member> {
+    let mut members: Vec<&Member> = leaderboard.members.values().collect();
+    members.sort_by(|a, b| scores[&b.id].cmp(&scores[&a.id])
+        .then(b.count_stars().cmp(&a.count_stars()))
+        .then(a.display_name().cmp(&b.display_name())));
+    members
+}
+
+pub fn render_leaderboard(leaderboard: &Leaderboard) -> Result<String, String> {
+    let year: i64 = leaderboard.event.parse().map_err(|_| format!("Invalid event: {}", leaderboard.event))?;
+    let scores = compute_local_scores(leaderboard);
+    let members = ranked_members(leaderboard, &scores);
+    let name_width = members.iter().map(|member| member.display_name().chars().count()).max().unwrap_or(4).max(4);
+
+    let mut output = format!("Private leaderboard {} ({} members)\n\n", leaderboard.event, members.len());
+    output += &format!("{:>4}  {:<name_width$}  {:>5}  {:>5}  ", "Rank", "Name", "Score", "Stars");
+    for day in 1..=25 {
+        output += &format!("{:>2} ", day);
+    }
+    output = output.trim_end().to_string() + "\n";
+
+    for (rank, member) in members.iter().enumerate() {
+        output += &format!("{:>4}  {:<name_width$}  {:>5}  {:>5}  ", rank + 1, member.display_name(), scores[&member.id], member.count_stars());
+        for day in 1..=25 {
+            let symbol = match (member.star_timestamp(day, 1), member.star_timestamp(day, 2)) {
+                (Some(_), Some(_)) => "★",
+                (Some(_), None) => "☆",
+                _ => " "
+            };
+            output += &format!("{:>2} ", symbol);
+        }
+        output = output.trim_end().to_string() + "\n";
+    }
+
+    for day in 1..=25 {
+        let unlock = unlock_timestamp(year, day);
+        let mut finishers: Vec<(&Member, i64, Option<i64>)> = members.iter()
+            .filter_map(|member| member.star_timestamp(day, 1).map(|first| (*member, first, member.star_timestamp(day, 2))))
+            .collect();
+        if finishers.is_empty() {
+            continue;
+        }
+        finishers.sort_by_key(|(_, first, second)| (second.is_none(), second.unwrap_or(*first), *first));
+
+        output += &format!("\nDay {}\n", day);
+        output += &format!("  {:<name_width$}  {:>12}  {:>12}  {:>12}\n", "Name", "Part 1", "Part 2", "Delta");
+        for (member, first, second) in finishers {
+            let (part2, delta) = match second {
+                Some(second) => (format_duration(second - unlock), format_duration(second - first)),
+                None => (String::new(), String::new())
+            };
+            output += &format!("  {:<name_width$}  {:>12}  {:>12}  {:>12}\n", member.display_name(), format_duration(first - unlock), part2, delta);
+        }
+    }
+
+    Ok(output.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Day 1 of 2024 unlocked at 1733029200.
+    const EXAMPLE: &str = r#"{
+        "event": "2024",
+        "owner_id": 1,
+        "members": {
+            "1": { "id": 1, "name": "alice", "stars": 3, "local_score": 0, "completion_day_level": {
+                "1": { "1": { "get_star_ts": 1733029500, "star_index": 0 }, "2": { "get_star_ts": 1733029800, "star_index": 1 } },
+                "2": { "1": { "get_star_ts": 1733117000, "star_index": 2 } }
+            } },
+            "2": { "id": 2, "name": null, "stars": 2, "local_score": 0, "completion_day_level": {
+                "1": { "1": { "get_star_ts": 1733029400, "star_index": 0 }, "2": { "get_star_ts": 1733040000, "star_index": 1 } }
+            } }
+        }
+    }"#;
+
+    #[test]
+    fn given_first_of_december_unlock_is_at_five_utc() {
+        assert_eq!(unlock_timestamp(2024, 1), 1733029200);
+    }
+
+    #[test]
+    fn given_example_local_scores_reward_earlier_stars() {
+        let leaderboard = parse_leaderboard(EXAMPLE).unwrap();
+
+        let scores = compute_local_scores(&leaderboard);
+
+        assert_eq!(scores[&1], 1 + 2 + 2);
+        assert_eq!(scores[&2], 2 + 1);
+    }
+
+    #[test]
+    fn given_example_render_shows_times_and_deltas() {
+        let leaderboard = parse_leaderboard(EXAMPLE).unwrap();
+
+        let result = render_leaderboard(&leaderboard).unwrap();
+
+        assert!(result.contains("(anonymous user #2)"));
+        assert!(result.contains("0:05:00       0:10:00       0:05:00"));
+        assert!(result.contains("0:03:20       3:00:00       2:56:40"));
+    }
+
+    #[test]
+    fn given_long_duration_days_are_shown() {
+        assert_eq!(format_duration(90061), "1d 01:01:01");
+    }
+
+    #[test]
+    fn given_negative_duration_it_is_clamped_to_zero() {
+        assert_eq!(format_duration(-3725), "0:00:00");
+    }
+
+    #[test]
+    fn given_other_year_event_check_fails() {
+        let leaderboard = parse_leaderboard(EXAMPLE).unwrap();
+
+        assert_eq!(check_event(&leaderboard, 2024), Ok(()));
+        assert_eq!(check_event(&leaderboard, 2023), Err(String::from("The leaderboard is for 2024, not 2023")));
+    }
+}
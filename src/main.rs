@@ -1,3 +1,222 @@
+mod answers;
+mod config;
+mod http;
+mod input;
+mod leaderboard;
+mod progress;
+mod solutions;
+
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use config::{Config, OutputFormat};
+use input::Input;
+use solutions::{ReportOptions, SolveOptions};
+
+const USAGE: &str = "Usage:
+    aoc2024 run <day> [input file, or - for stdin] [--year <year>] [--strategy <name>] [--format text|json]
+    aoc2024 report <day> <report> [input file, or - for stdin] [--year <year>] [--format text|json] [--update <n>]
+    aoc2024 fetch <day> [--year <year>]
+    aoc2024 progress [--year <year>] [--markdown]
+    aoc2024 leaderboard <leaderboard json file> [--year <year>]
+    aoc2024 leaderboard --fetch <leaderboard id> [--year <year>]
+    aoc2024 config
+    aoc2024 help";
+
 fn main() {
-    
-}
\ No newline at end of file
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // Only the commands that use the configuration load it, so a broken aoc.toml does not hide the usage.
+    let result = match args.first().map(String::as_str) {
+        Some("run") => Config::load().and_then(|config| run(&config, &args[1..])),
+        Some("report") => Config::load().and_then(|config| report(&config, &args[1..])),
+        Some("fetch") => Config::load().and_then(|config| fetch(&config, &args[1..])),
+        Some("progress") => Config::load().and_then(|config| show_progress(&config, &args[1..])),
+        Some("leaderboard") => show_leaderboard(&args[1..]),
+        Some("config") => Config::load().map(|config| println!("{}", config)),
+        Some("help" | "--help" | "-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(String::from(USAGE))
+    };
+
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run(config: &Config, args: &[String]) -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut year = config.year;
+    let mut strategy = None;
+    let mut format = config.output_format;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--year" => year = parse_year(args.next().ok_or(USAGE)?)?,
+            "--strategy" => strategy = Some(args.next().ok_or(USAGE)?.as_str()),
+            "--format" => format = OutputFormat::parse(args.next().ok_or(USAGE)?)?,
+            _ => positional.push(arg.as_str())
+        }
+    }
+    if positional.is_empty() || positional.len() > 2 {
+        return Err(String::from(USAGE));
+    }
+
+    let day: u8 = positional[0].parse().map_err(|_| format!("Invalid day: {}", positional[0]))?;
+    let solution = solutions::get_solution(year, day).ok_or(format!("{} day {} is not solved yet", year, day))?;
+    let strategy = solution.select_strategy(strategy.or(config.strategy_for_day(year, day)))?;
+    let input = select_input(config, year, day, positional.get(1))?;
+
+    let mut reader = input.open().map_err(|e| format!("Could not open {}: {}", input, e))?;
+    let options = SolveOptions { strategy, rules: config.rules_for_day(year, day) };
+    let answers = (solution.solve)(&mut reader, &options).map_err(|e| format!("Could not read {}: {}", input, e))?;
+
+    match format {
+        OutputFormat::Text => {
+            for answer in answers {
+                println!("{} day {} part {}: {}", year, day, answer.part, answer.value);
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::json!({ "year": year, "day": day, "strategy": strategy, "answers": answers }));
+        }
+    }
+
+    Ok(())
+}
+
+fn report(config: &Config, args: &[String]) -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut year = config.year;
+    let mut options = ReportOptions { format: config.output_format, update: None };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--year" => year = parse_year(args.next().ok_or(USAGE)?)?,
+            "--format" => options.format = OutputFormat::parse(args.next().ok_or(USAGE)?)?,
+            "--update" => {
+                let update = args.next().ok_or(USAGE)?;
+                options.update = Some(update.parse().map_err(|_| format!("Invalid update: {}", update))?);
+            }
+            _ => positional.push(arg.as_str())
+        }
+    }
+    if positional.len() < 2 || positional.len() > 3 {
+        return Err(String::from(USAGE));
+    }
+
+    let day: u8 = positional[0].parse().map_err(|_| format!("Invalid day: {}", positional[0]))?;
+    let solution = solutions::get_solution(year, day).ok_or(format!("{} day {} is not solved yet", year, day))?;
+    let report = solution.get_report(positional[1])?;
+    report.check_options(&options)?;
+    let input = select_input(config, year, day, positional.get(2))?;
+
+    let mut reader = input.open().map_err(|e| format!("Could not open {}: {}", input, e))?;
+    let output = (report.render)(&mut reader, &options).map_err(|e| match e.kind() {
+        io::ErrorKind::InvalidInput => e.to_string(),
+        _ => format!("Could not read {}: {}", input, e)
+    })?;
+    println!("{}", output);
+
+    Ok(())
+}
+
+/// The input given on the command line, or else the one of the input directory.
+fn select_input(config: &Config, year: u16, day: u8, argument: Option<&&str>) -> Result<Input, String> {
+    match argument {
+        Some(argument) => Ok(Input::from_argument(argument)),
+        None => input::find_input(config, year, day)
+    }
+}
+
+/// Inputs are only downloaded when asked for, so running a day never talks to the site on its own.
+fn fetch(config: &Config, args: &[String]) -> Result<(), String> {
+    let mut day = None;
+    let mut year = config.year;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--year" => year = parse_year(args.next().ok_or(USAGE)?)?,
+            _ if day.is_none() => day = Some(arg.parse::<u8>().map_err(|_| format!("Invalid day: {}", arg))?),
+            _ => return Err(String::from(USAGE))
+        }
+    }
+    let day = day.ok_or(USAGE)?;
+    if !(1..=25).contains(&day) {
+        return Err(format!("Invalid day: {}", day));
+    }
+
+    match input::fetch_input(config, year, day)? {
+        (path, true) => println!("Downloaded {}", path.display()),
+        (path, false) => println!("{} is already there", path.display())
+    }
+
+    Ok(())
+}
+
+fn show_progress(config: &Config, args: &[String]) -> Result<(), String> {
+    let mut year = config.year;
+    let mut markdown = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--year" => year = parse_year(args.next().ok_or(USAGE)?)?,
+            "--markdown" => markdown = true,
+            _ => return Err(String::from(USAGE))
+        }
+    }
+
+    let days = progress::collect_progress(config, year)?;
+    match markdown {
+        true => println!("{}", progress::render_markdown(year, &days)),
+        false => println!("{}", progress::render_terminal(year, &days))
+    }
+
+    Ok(())
+}
+
+/// A leaderboard file is rendered without the configuration, fetching needs it for the session and cache.
+/// With a file, `--year` checks the event the file is for.
+fn show_leaderboard(args: &[String]) -> Result<(), String> {
+    let mut year = None;
+    let mut fetch_id = None;
+    let mut file = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--year" => year = Some(parse_year(args.next().ok_or(USAGE)?)?),
+            "--fetch" => fetch_id = Some(args.next().ok_or(USAGE)?),
+            _ if file.is_none() => file = Some(arg),
+            _ => return Err(String::from(USAGE))
+        }
+    }
+
+    let leaderboard = match (fetch_id, file) {
+        (Some(id), None) => {
+            let config = Config::load()?;
+            leaderboard::fetch_leaderboard(&config, year.unwrap_or(config.year), id)?
+        }
+        (None, Some(file)) => leaderboard::load_leaderboard(Path::new(file))?,
+        _ => return Err(String::from(USAGE))
+    };
+    if let Some(year) = year {
+        leaderboard::check_event(&leaderboard, year)?;
+    }
+    println!("{}", leaderboard::render_leaderboard(&leaderboard)?);
+
+    Ok(())
+}
+
+fn parse_year(value: &str) -> Result<u16, String> {
+    value.parse().map_err(|_| format!("Invalid year: {}", value))
+}
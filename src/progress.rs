@@ -0,0 +1,244 @@
+use std::time::{Duration, Instant};
+
+use crate::answers::Answers;
+use crate::config::Config;
+use crate::input::Input;
+use crate::solutions::{self, Solution, SolveOptions};
+
+const DAYS: u8 = 25;
+const PARTS: [u8; 2] = [1, 2];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PartStatus {
+    NotImplemented,
+    /// Implemented, but there is no input or no recorded answer to check it against.
+    Unverified,
+    Verified,
+    Wrong
+}
+
+impl PartStatus {
+    fn symbol(&self) -> &'static str {
+        match self {
+            PartStatus::NotImplemented => " ",
+            PartStatus::Unverified => "☆",
+            PartStatus::Verified => "★",
+            PartStatus::Wrong => "✗"
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Runtime {
+    NotRun,
+    Ran(Duration),
+    Failed(String)
+}
+
+pub struct DayProgress {
+    pub day: u8,
+    pub has_crate: bool,
+    pub parts: [PartStatus; 2],
+    pub runtime: Runtime
+}
+
+/// Runs every solution of the year that has an input, and compares its answers with the answers file.
+pub fn collect_progress(config: &Config, year: u16) -> Result<Vec<DayProgress>, String> {
+    let known_year = solutions::get_year(year).ok_or(format!("Year {} is not known", year))?;
+    let answers = Answers::load(&config.answers_path(year))?;
+
+    let mut progress = Vec::new();
+    for day in 1..=DAYS {
+        let has_crate = known_year.has_crate(&config.root_dir, day);
+        let day_progress = match solutions::get_solution(year, day) {
+            None => DayProgress { day, has_crate, parts: [PartStatus::NotImplemented; 2], runtime: Runtime::NotRun },
+            Some(solution) => check_solution(config, solution, &answers, has_crate)
+        };
+        progress.push(day_progress);
+    }
+
+    Ok(progress)
+}
+
+fn check_solution(config: &Config, solution: &Solution, answers: &Answers, has_crate: bool) -> DayProgress {
+    let mut parts = PARTS.map(|part| match solution.parts.contains(&part) {
+        true => PartStatus::Unverified,
+        false => PartStatus::NotImplemented
+    });
+
+    let input = Input::File(config.input_path(solution.year, solution.day));
+    let mut reader = match input.open() {
+        Ok(reader) => reader,
+        Err(_) => return DayProgress { day: solution.day, has_crate, parts, runtime: Runtime::NotRun }
+    };
+    let strategy = match solution.select_strategy(config.strategy_for_day(solution.year, solution.day)) {
+        Ok(strategy) => strategy,
+        Err(message) => return DayProgress { day: solution.day, has_crate, parts, runtime: Runtime::Failed(message) }
+    };
+
+    let start = Instant::now();
+    let options = SolveOptions { strategy, rules: config.rules_for_day(solution.year, solution.day) };
+    let result = (solution.solve)(&mut reader, &options);
+    let elapsed = start.elapsed();
+
+    let computed = match result {
+        Ok(computed) => computed,
+        Err(e) => return DayProgress { day: solution.day, has_crate, parts, runtime: Runtime::Failed(e.to_string()) }
+    };
+    for answer in computed {
+        let index = match PARTS.iter().position(|part| *part == answer.part) {
+            Some(index) => index,
+            None => continue
+        };
+        parts[index] = match answers.get(solution.day, answer.part) {
+            None => PartStatus::Unverified,
+            Some(expected) if expected == answer.value => PartStatus::Verified,
+            Some(_) => PartStatus::Wrong
+        };
+    }
+
+    DayProgress { day: solution.day, has_crate, parts, runtime: Runtime::Ran(elapsed) }
+}
+
+fn count_stars(progress: &[DayProgress]) -> usize {
+    progress.iter()
+        .flat_map(|day| day.parts.iter())
+        .filter(|status| **status == PartStatus::Verified)
+        .count()
+}
+
+fn format_runtime(runtime: &Runtime) -> String {
+    match runtime {
+        Runtime::NotRun => String::new(),
+        Runtime::Ran(elapsed) => format!("{:.2?}", elapsed),
+        Runtime::Failed(_) => String::from("failed")
+    }
+}
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const CELL_WIDTH: usize = 10;
+
+/// The day of the week of December 1st, 0 for Sunday, by Sakamoto's method.
+fn first_weekday(year: u16) -> usize {
+    let year = year as usize;
+    (year + year / 4 - year / 100 + year / 400 + 4 + 1) % 7
+}
+
+/// The days laid out as on a wall calendar, one row per week from Sunday to Saturday.
+fn calendar_weeks(year: u16, progress: &[DayProgress]) -> Vec<[Option<&DayProgress>; 7]> {
+    let offset = first_weekday(year);
+    let mut weeks = vec![[None; 7]; (offset + DAYS as usize).div_ceil(7)];
+    for day in progress {
+        let slot = offset + day.day as usize - 1;
+        weeks[slot / 7][slot % 7] = Some(day);
+    }
+    weeks
+}
+
+fn format_stars(day: &DayProgress) -> String {
+    format!("{}{}", day.parts[0].symbol(), day.parts[1].symbol())
+}
+
+/// The runtime, or whether the day has a crate when it was not run.
+fn format_details(day: &DayProgress) -> String {
+    match (&day.runtime, day.has_crate) {
+        (Runtime::NotRun, false) => String::from("no crate"),
+        (runtime, _) => format_runtime(runtime)
+    }
+}
+
+pub fn render_terminal(year: u16, progress: &[DayProgress]) -> String {
+    let mut output = format!("Advent of Code {}  {}/{} ★\n\n", year, count_stars(progress), DAYS as usize * PARTS.len());
+    let header: String = WEEKDAYS.iter().map(|weekday| format!("{:<width$}", weekday, width = CELL_WIDTH)).collect();
+    output += header.trim_end();
+
+    for week in calendar_weeks(year, progress) {
+        let mut stars_line = String::new();
+        let mut details_line = String::new();
+        for cell in week {
+            let (stars, details) = match cell {
+                Some(day) => (format!("{:>2} {}", day.day, format_stars(day)), format_details(day)),
+                None => (String::new(), String::new())
+            };
+            stars_line += &format!("{:<width$}", stars, width = CELL_WIDTH);
+            details_line += &format!("{:<width$}", details, width = CELL_WIDTH);
+        }
+        output += &format!("\n{}\n{}", stars_line.trim_end(), details_line.trim_end());
+    }
+    output += "\n";
+
+    for day in progress {
+        if let Runtime::Failed(message) = &day.runtime {
+            output += &format!("\nDay {} failed: {}", day.day, message);
+        }
+    }
+
+    output += "\n★ verified  ☆ not verified  ✗ wrong answer";
+    output
+}
+
+pub fn render_markdown(year: u16, progress: &[DayProgress]) -> String {
+    let mut output = format!("## Advent of Code {} ({}/{} ★)\n\n", year, count_stars(progress), DAYS as usize * PARTS.len());
+    output += &format!("| {} |\n", WEEKDAYS.join(" | "));
+    output += &format!("|{}\n", ":---:|".repeat(WEEKDAYS.len()));
+
+    for week in calendar_weeks(year, progress) {
+        let cells: Vec<String> = week.iter().map(|cell| match cell {
+            Some(day) => format!("{}<br>{}", format!("**{}** {}", day.day, format_stars(day)).trim_end(), format_details(day)),
+            None => String::new()
+        }).collect();
+        output += &format!("| {} |\n", cells.join(" | "));
+    }
+
+    output += "\n★ verified, ☆ not verified, ✗ wrong answer";
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_progress() -> Vec<DayProgress> {
+        vec![
+            DayProgress { day: 1, has_crate: true, parts: [PartStatus::Verified, PartStatus::Verified], runtime: Runtime::Ran(Duration::from_millis(2)) },
+            DayProgress { day: 2, has_crate: true, parts: [PartStatus::NotImplemented, PartStatus::Wrong], runtime: Runtime::Failed(String::from("bad input")) },
+            DayProgress { day: 3, has_crate: false, parts: [PartStatus::NotImplemented; 2], runtime: Runtime::NotRun }
+        ]
+    }
+
+    #[test]
+    fn given_progress_markdown_lays_days_out_by_week() {
+        let result = render_markdown(2024, &example_progress());
+
+        assert!(result.starts_with("## Advent of Code 2024 (2/50 ★)"));
+        assert!(result.contains("| Sun | Mon | Tue | Wed | Thu | Fri | Sat |"));
+        assert!(result.contains("\n| **1** ★★<br>2.00ms | **2**  ✗<br>failed | **3**<br>no crate |  |  |  |  |\n"));
+        assert_eq!(result.lines().filter(|line| line.starts_with('|')).count(), 6);
+    }
+
+    #[test]
+    fn given_year_first_of_december_falls_on_its_weekday() {
+        assert_eq!(first_weekday(2023), 5);
+        assert_eq!(first_weekday(2024), 0);
+        assert_eq!(first_weekday(2025), 1);
+    }
+
+    #[test]
+    fn given_progress_terminal_puts_days_under_their_weekday() {
+        let result = render_terminal(2023, &example_progress());
+
+        assert!(result.contains("Sun       Mon       Tue       Wed       Thu       Fri       Sat
+                                                   1 ★★      2  ✗
+                                                  2.00ms    failed
+ 3
+no crate
+"));
+    }
+
+    #[test]
+    fn given_failed_day_terminal_shows_reason() {
+        let result = render_terminal(2024, &example_progress());
+
+        assert!(result.contains("Day 2 failed: bad input"));
+    }
+}
@@ -0,0 +1,144 @@
+mod year2024;
+
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::config::OutputFormat;
+
+#[derive(Serialize)]
+pub struct Answer {
+    pub part: u8,
+    pub value: String
+}
+
+/// How `aoc2024 run` was asked to solve.
+pub struct SolveOptions<'a> {
+    /// Already checked against `Solution::strategies`.
+    pub strategy: Option<&'a str>,
+    /// The rule file of the days that read one, see `Config::rules_for_day`.
+    pub rules: Option<&'a Path>
+}
+
+/// A day reads its input once and answers every part it implements, since stdin cannot be rewound.
+pub type Solver = fn(&mut dyn BufRead, &SolveOptions) -> io::Result<Vec<Answer>>;
+
+/// How `aoc2024 report` was asked to render.
+pub struct ReportOptions {
+    pub format: OutputFormat,
+    /// One update of the input to report on, counting from 1, for the reports that look at a single one.
+    pub update: Option<usize>
+}
+
+/// Renders a day specific view of the input, such as an audit or a visualisation, instead of the answers.
+/// An update the input does not have is an `InvalidInput` error.
+pub type Reporter = fn(&mut dyn BufRead, &ReportOptions) -> io::Result<String>;
+
+/// A report available through `aoc2024 report`, with the options it can honour.
+pub struct Report {
+    pub name: &'static str,
+    pub render: Reporter,
+    pub formats: &'static [OutputFormat],
+    /// Whether the report can look at a single update chosen with `--update`.
+    pub takes_update: bool
+}
+
+impl Report {
+
+    /// Options the report cannot honour are refused before any input is read.
+    pub fn check_options(&self, options: &ReportOptions) -> Result<(), String> {
+        if !self.formats.contains(&options.format) {
+            return Err(format!("The {} report has no {} format", self.name, options.format));
+        }
+        if options.update.is_some() && !self.takes_update {
+            return Err(format!("The {} report does not take --update", self.name));
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Solution {
+    pub year: u16,
+    pub day: u8,
+    /// The parts the solver answers, used to tell which stars are within reach.
+    pub parts: &'static [u8],
+    /// Alternative implementations the day can be run with, the first one is the default.
+    pub strategies: &'static [&'static str],
+    pub solve: Solver,
+    pub reports: &'static [Report]
+}
+
+impl Solution {
+
+    /// Picks the strategy to run with, `None` when the day has no alternatives.
+    pub fn select_strategy<'a>(&self, requested: Option<&'a str>) -> Result<Option<&'a str>, String> {
+        match requested {
+            None => Ok(self.strategies.first().copied()),
+            Some(strategy) if self.strategies.contains(&strategy) => Ok(Some(strategy)),
+            Some(strategy) if self.strategies.is_empty() => Err(format!("{} day {} has no strategies, got {}", self.year, self.day, strategy)),
+            Some(strategy) => Err(format!("Unknown strategy {} for {} day {} (expected one of {})", strategy, self.year, self.day, self.strategies.join(", ")))
+        }
+    }
+
+    pub fn get_report(&self, name: &str) -> Result<&Report, String> {
+        match self.reports.iter().find(|report| report.name == name) {
+            Some(report) => Ok(report),
+            None if self.reports.is_empty() => Err(format!("{} day {} has no reports", self.year, self.day)),
+            None => {
+                let names: Vec<&str> = self.reports.iter().map(|report| report.name).collect();
+                Err(format!("Unknown report {} for {} day {} (expected one of {})", name, self.year, self.day, names.join(", ")))
+            }
+        }
+    }
+}
+
+pub struct Year {
+    pub year: u16,
+    /// Where the `dayN` crates of the event live, relative to `Config::root_dir`.
+    pub crates_dir: &'static str,
+    pub solutions: &'static [Solution]
+}
+
+impl Year {
+
+    pub fn has_crate(&self, root_dir: &Path, day: u8) -> bool {
+        self.crate_path(root_dir, day).join("Cargo.toml").is_file()
+    }
+
+    fn crate_path(&self, root_dir: &Path, day: u8) -> PathBuf {
+        root_dir.join(self.crates_dir).join(format!("day{}", day))
+    }
+}
+
+/// Every event the runner knows about.
+const YEARS: &[Year] = &[
+    Year { year: 2024, crates_dir: ".", solutions: year2024::SOLUTIONS }
+];
+
+pub fn get_year(year: u16) -> Option<&'static Year> {
+    YEARS.iter().find(|known_year| known_year.year == year)
+}
+
+pub fn get_solution(year: u16, day: u8) -> Option<&'static Solution> {
+    get_year(year)?.solutions.iter().find(|solution| solution.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(day: u8, name: &str, format: OutputFormat, update: Option<usize>) -> Result<(), String> {
+        get_solution(2024, day).unwrap().get_report(name)?.check_options(&ReportOptions { format, update })
+    }
+
+    #[test]
+    fn given_options_a_report_cannot_honour_they_are_refused() {
+        assert_eq!(check(3, "highlight", OutputFormat::Json, None), Err(String::from("The highlight report has no json format")));
+        assert_eq!(check(4, "render", OutputFormat::Text, Some(3)), Err(String::from("The render report does not take --update")));
+        assert_eq!(check(5, "dot", OutputFormat::Json, Some(3)), Err(String::from("The dot report has no json format")));
+        assert_eq!(check(5, "dot", OutputFormat::Text, Some(3)), Ok(()));
+        assert_eq!(check(1, "reconcile", OutputFormat::Json, None), Ok(()));
+    }
+}
@@ -0,0 +1,289 @@
+use std::io::{self, BufRead};
+
+use super::{Answer, Report, ReportOptions, Solution, SolveOptions};
+use crate::config::OutputFormat;
+
+const TEXT: &[OutputFormat] = &[OutputFormat::Text];
+const TEXT_AND_JSON: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+pub const SOLUTIONS: &[Solution] = &[
+    Solution {
+        year: 2024, day: 1, parts: &[1, 2],
+        strategies: &["frequency", "nested", "external"],
+        solve: solve_day1,
+        reports: &[
+            Report { name: "reconcile", render: report_day1_reconciliation, formats: TEXT_AND_JSON, takes_update: false }
+        ]
+    },
+    Solution {
+        year: 2024, day: 2, parts: &[1, 2],
+        strategies: &["validator", "rules"],
+        solve: solve_day2,
+        reports: &[
+            Report { name: "explain", render: report_day2_explanations, formats: TEXT_AND_JSON, takes_update: false }
+        ]
+    },
+    Solution {
+        year: 2024, day: 3, parts: &[1, 2],
+        strategies: &["streaming", "tokens", "instructions"],
+        solve: solve_day3,
+        reports: &[
+            Report { name: "disassemble", render: report_day3_disassembly, formats: TEXT_AND_JSON, takes_update: false },
+            Report { name: "highlight", render: report_day3_highlight, formats: TEXT, takes_update: false },
+            Report { name: "html", render: report_day3_html, formats: TEXT, takes_update: false }
+        ]
+    },
+    Solution {
+        year: 2024, day: 4, parts: &[1, 2],
+        strategies: &["words", "template", "wrapping"],
+        solve: solve_day4,
+        reports: &[
+            Report { name: "render", render: report_day4_render, formats: TEXT, takes_update: false },
+            Report { name: "highlight", render: report_day4_highlight, formats: TEXT, takes_update: false }
+        ]
+    },
+    Solution {
+        year: 2024, day: 5, parts: &[1, 2],
+        strategies: &["rule_set", "page_orders"],
+        solve: solve_day5,
+        reports: &[
+            Report { name: "explain", render: report_day5_explanations, formats: TEXT_AND_JSON, takes_update: false },
+            Report { name: "dot", render: report_day5_dot, formats: TEXT, takes_update: true },
+            Report { name: "mermaid", render: report_day5_mermaid, formats: TEXT, takes_update: true }
+        ]
+    }
+];
+
+fn invalid_day1_lists(error: day1::ListsError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+/// `frequency` counts the right list in a map, `nested` compares every pair of ids,
+/// `external` sorts through temporary files for lists that do not fit in memory.
+fn solve_day1(reader: &mut dyn BufRead, options: &SolveOptions) -> io::Result<Vec<Answer>> {
+    if options.strategy == Some("external") {
+        let totals = day1::external_sort::calculate_with_external_sort(reader, day1::external_sort::DEFAULT_RUN_SIZE)?;
+
+        return Ok(vec![
+            Answer { part: 1, value: totals.distance.to_string() },
+            Answer { part: 2, value: totals.similarity.to_string() }
+        ]);
+    }
+
+    let lists = day1::read_santas_lists(reader)?;
+    let similarity = match options.strategy {
+        Some("nested") => day1::calculate_similarity_for_lists(&lists).map(i64::from),
+        _ => day1::calculate_similarity_with_frequencies(&lists)
+    }.map_err(invalid_day1_lists)?;
+    let distance = day1::calculate_distance_for_lists(&lists).map_err(invalid_day1_lists)?;
+
+    Ok(vec![
+        Answer { part: 1, value: distance.to_string() },
+        Answer { part: 2, value: similarity.to_string() }
+    ])
+}
+
+fn report_day1_reconciliation(reader: &mut dyn BufRead, options: &ReportOptions) -> io::Result<String> {
+    let lists = day1::read_santas_lists(reader)?;
+    let report = day1::reconcile_lists(&lists).map_err(invalid_day1_lists)?;
+
+    Ok(match options.format {
+        OutputFormat::Text => report.to_text(),
+        OutputFormat::Json => report.to_json()
+    })
+}
+
+/// `validator` checks the puzzle's rules directly, `rules` checks the rule file of the configuration
+/// (see `day2::rules::SequenceRule`), or the puzzle's rules without one, with the Problem Dampener for part 2.
+fn solve_day2(reader: &mut dyn BufRead, options: &SolveOptions) -> io::Result<Vec<Answer>> {
+    let safe_reports = match options.strategy {
+        Some("rules") => {
+            let rule = match options.rules {
+                Some(path) => day2::rules::load_rules(path).map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))?,
+                None => day2::rules::SequenceRule::puzzle_rules()
+            };
+            day2::rules::count_satisfying_reports_from_reader(reader, &rule, &[0, day2::PROBLEM_DAMPENER.tolerance])?
+        }
+        _ => day2::count_safe_reports_from_reader(reader, &[day2::STRICT_RULES, day2::PROBLEM_DAMPENER])?
+    };
+
+    Ok(vec![
+        Answer { part: 1, value: safe_reports[0].to_string() },
+        Answer { part: 2, value: safe_reports[1].to_string() }
+    ])
+}
+
+fn report_day2_explanations(reader: &mut dyn BufRead, options: &ReportOptions) -> io::Result<String> {
+    let explanations = day2::explain_reports_from_reader(reader, &day2::PROBLEM_DAMPENER)?;
+
+    Ok(match options.format {
+        OutputFormat::Text => day2::format_explanations(&explanations),
+        OutputFormat::Json => day2::explanations_to_json(&explanations)
+    })
+}
+
+/// `streaming` scans the memory in chunks as it is read, `tokens` reads all of it and tokenizes it first,
+/// `instructions` runs it with the instruction sets of `day3::instructions`.
+fn solve_day3(reader: &mut dyn BufRead, options: &SolveOptions) -> io::Result<Vec<Answer>> {
+    match options.strategy {
+        Some("tokens") => {
+            let memory = day3::read_memory(reader)?;
+
+            Ok(vec![
+                Answer { part: 1, value: day3::calculate_result_of_all_multiplications(&memory).to_string() },
+                Answer { part: 2, value: day3::calculate_result_of_multiplications(&memory).to_string() }
+            ])
+        }
+        Some("instructions") => {
+            let memory = day3::read_memory(reader)?;
+            let run = |instruction_set: day3::instructions::InstructionSet| instruction_set.run(&memory)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()));
+
+            Ok(vec![
+                Answer { part: 1, value: run(day3::instructions::InstructionSet::multiplications())?.sum.to_string() },
+                Answer { part: 2, value: run(day3::instructions::InstructionSet::puzzle())?.sum.to_string() }
+            ])
+        }
+        _ => {
+            let totals = day3::streaming::calculate_multiplications_streaming(reader, day3::streaming::DEFAULT_CHUNK_SIZE)?;
+
+            Ok(vec![
+                Answer { part: 1, value: totals.all.to_string() },
+                Answer { part: 2, value: totals.enabled.to_string() }
+            ])
+        }
+    }
+}
+
+fn report_day3_disassembly(reader: &mut dyn BufRead, options: &ReportOptions) -> io::Result<String> {
+    let instructions = day3::disassembly::disassemble(&day3::read_memory(reader)?);
+
+    Ok(match options.format {
+        OutputFormat::Text => day3::disassembly::format_disassembly(&instructions),
+        OutputFormat::Json => day3::disassembly::disassembly_to_json(&instructions)
+    })
+}
+
+/// The memory with its instructions coloured for a terminal.
+fn report_day3_highlight(reader: &mut dyn BufRead, _options: &ReportOptions) -> io::Result<String> {
+    let memory = day3::read_memory(reader)?;
+
+    Ok(day3::disassembly::highlight_ansi(&memory, &day3::disassembly::disassemble(&memory)))
+}
+
+/// The memory with its instructions coloured as an HTML page.
+fn report_day3_html(reader: &mut dyn BufRead, _options: &ReportOptions) -> io::Result<String> {
+    let memory = day3::read_memory(reader)?;
+
+    Ok(day3::disassembly::highlight_html(&memory, &day3::disassembly::disassemble(&memory)))
+}
+
+/// `words` finds part 2 as crossing `MAS`, `template` as the rotations of an X-MAS template.
+/// `wrapping` is `words` with the edges of the grid joined for part 1, so an `XMAS` can go off one side and come back on the other.
+fn solve_day4(reader: &mut dyn BufRead, options: &SolveOptions) -> io::Result<Vec<Answer>> {
+    let mut grid = String::new();
+    reader.read_to_string(&mut grid)?;
+    let words = match options.strategy {
+        Some("wrapping") => day4::count_xmas_wrapping(&grid),
+        _ => day4::count_xmas(&grid)
+    };
+    let crossings = match options.strategy {
+        Some("template") => day4::count_x_mas_with_template(&grid),
+        _ => day4::find_patterns_xmas(&grid) as usize
+    };
+
+    Ok(vec![
+        Answer { part: 1, value: words.to_string() },
+        Answer { part: 2, value: crossings.to_string() }
+    ])
+}
+
+fn find_day4_xmas(reader: &mut dyn BufRead) -> io::Result<(Vec<Vec<char>>, Vec<day4::WordMatch<'static>>)> {
+    let mut grid = String::new();
+    reader.read_to_string(&mut grid)?;
+    let lines = day4::read_all_lines(&grid);
+    let matches = day4::multi_search::find_words_fast(&lines, &["XMAS"]);
+
+    Ok((lines, matches))
+}
+
+/// The grid with only the letters of each `XMAS`.
+fn report_day4_render(reader: &mut dyn BufRead, _options: &ReportOptions) -> io::Result<String> {
+    let (lines, matches) = find_day4_xmas(reader)?;
+
+    Ok(day4::render::render_found_words(&lines, &matches))
+}
+
+/// Same as `render`, coloured for a terminal.
+fn report_day4_highlight(reader: &mut dyn BufRead, _options: &ReportOptions) -> io::Result<String> {
+    let (lines, matches) = find_day4_xmas(reader)?;
+
+    Ok(day4::render::render_found_words_ansi(&lines, &matches))
+}
+
+fn read_day5_manual(reader: &mut dyn BufRead) -> io::Result<day5::rule_set::Manual> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+
+    day5::rule_set::parse_manual(&input).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// `rule_set` looks the rules up in a set of page pairs, `page_orders` goes through the list of rules for every pair of pages.
+/// Either way, rules forming a cycle within an update are an error.
+fn solve_day5(reader: &mut dyn BufRead, options: &SolveOptions) -> io::Result<Vec<Answer>> {
+    let (correct, corrected) = match options.strategy {
+        Some("page_orders") => {
+            let mut input = String::new();
+            reader.read_to_string(&mut input)?;
+            let (correct, corrected) = day5::get_sums_of_page_lists(&input).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            (correct as u64, corrected as u64)
+        }
+        _ => {
+            let sums = read_day5_manual(reader)?
+                .sum_middle_pages()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            (sums.correct, sums.corrected)
+        }
+    };
+
+    Ok(vec![
+        Answer { part: 1, value: correct.to_string() },
+        Answer { part: 2, value: corrected.to_string() }
+    ])
+}
+
+fn report_day5_explanations(reader: &mut dyn BufRead, options: &ReportOptions) -> io::Result<String> {
+    let explanations = day5::explain::explain_updates(&read_day5_manual(reader)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    Ok(match options.format {
+        OutputFormat::Text => day5::explain::format_explanations(&explanations),
+        OutputFormat::Json => day5::explain::explanations_to_json(&explanations)
+    })
+}
+
+/// The rule graph for Graphviz: every rule, or with `--update` only the pages of that update and its broken rules in red.
+fn report_day5_dot(reader: &mut dyn BufRead, options: &ReportOptions) -> io::Result<String> {
+    let manual = read_day5_manual(reader)?;
+
+    Ok(day5::graph::rules_to_dot(&manual.rules, select_day5_update(&manual, options)?))
+}
+
+/// Same as `dot`, as a Mermaid flowchart.
+fn report_day5_mermaid(reader: &mut dyn BufRead, options: &ReportOptions) -> io::Result<String> {
+    let manual = read_day5_manual(reader)?;
+
+    Ok(day5::graph::rules_to_mermaid(&manual.rules, select_day5_update(&manual, options)?))
+}
+
+/// The update asked for with `--update`, for the graph reports.
+fn select_day5_update<'a>(manual: &'a day5::rule_set::Manual, options: &ReportOptions) -> io::Result<Option<&'a [u32]>> {
+    match options.update {
+        None => Ok(None),
+        Some(update) => update.checked_sub(1)
+            .and_then(|index| manual.updates.get(index))
+            .map(|pages| Some(pages.as_slice()))
+            .ok_or(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("Invalid update: {} (expected 1 to {})", update, manual.updates.len())))
+    }
+}